@@ -7,7 +7,79 @@ use crate::framework::mesh::vertex::{
 use crate::framework::util::math::f32::PHI;
 use glam::{Vec2, Vec3};
 use obj::{load_obj, Obj, ObjError, TexturedVertex};
-use std::f32::consts::TAU;
+use std::collections::HashMap;
+use std::f32::consts::{PI, TAU};
+
+// the vertex positions and faces of an icosahedron, whose vertices are the corners of three
+// orthogonal golden rectangles
+fn icosahedron() -> (Vec<Vec3>, Vec<[u32; 3]>) {
+    let inv_phi = 1. / PHI;
+
+    #[rustfmt::skip]
+    let vertex_positions = vec![
+        Vec3::new(0., inv_phi, -1.),
+        Vec3::new(inv_phi, 1., 0.),
+        Vec3::new(-inv_phi, 1., 0.),
+        Vec3::new(0., inv_phi, 1.),
+        Vec3::new(0., -inv_phi, 1.),
+        Vec3::new(-1., 0., inv_phi),
+        Vec3::new(0., -inv_phi, -1.),
+        Vec3::new( 1.,  0., -inv_phi),
+        Vec3::new(1., 0., inv_phi),
+        Vec3::new(-1.,  0., -inv_phi),
+        Vec3::new(inv_phi, -1., 0.),
+        Vec3::new(-inv_phi, -1., 0.),
+    ];
+
+    #[rustfmt::skip]
+    let faces = vec![
+        [ 2,  1,  0],
+        [ 1,  2,  3],
+        [ 5,  4,  3],
+        [ 4,  8,  3],
+        [ 7,  6,  0],
+        [ 6,  9,  0],
+        [11, 10,  4],
+        [10, 11,  6],
+        [ 9,  5,  2],
+        [ 5,  9, 11],
+        [ 8,  7,  1],
+        [ 7,  8, 10],
+        [ 2,  5,  3],
+        [ 8,  1,  3],
+        [ 9,  2,  0],
+        [ 1,  7,  0],
+        [11,  9,  6],
+        [ 7, 10,  6],
+        [ 5, 11,  4],
+        [10,  8,  4],
+    ];
+
+    (vertex_positions, faces)
+}
+
+// the faces of a grid of `num_rows + 1` rows with `num_columns + 1` vertices each, two per cell
+fn grid_faces(num_rows: usize, num_columns: usize) -> Vec<[u32; 3]> {
+    let row_length = num_columns as u32 + 1;
+    let mut faces = Vec::with_capacity(num_rows * num_columns * 2);
+    for j in 0..num_rows as u32 {
+        for i in 0..num_columns as u32 {
+            let (a, b) = (j * row_length + i, j * row_length + i + 1);
+            let (c, d) = (a + row_length, b + row_length);
+            faces.push([a, b, c]);
+            faces.push([b, d, c]);
+        }
+    }
+    faces
+}
+
+// removes faces whose vertices coincide, e.g., at the tip of a cone
+fn remove_degenerate_faces<V: Position>(faces: &mut Vec<[u32; 3]>, vertices: &[V]) {
+    faces.retain(|f| {
+        let [a, b, c] = f.map(|i| vertices[i as usize].position());
+        (b - a).cross(c - a).length_squared() > 0.
+    });
+}
 
 pub struct Mesh<V> {
     name: String,
@@ -99,60 +171,14 @@ impl<V: FromPositionNormal + Position> Mesh<V> {
     }
 
     pub fn new_icosahedron() -> Self {
-        let inv_phi = 1. / PHI;
-
         // todo: add texture coordinates
-        #[rustfmt::skip]
-            let vertex_positions = vec![
-            Vec3::new(0., inv_phi, -1.),
-            Vec3::new(inv_phi, 1., 0.),
-            Vec3::new(-inv_phi, 1., 0.),
-            Vec3::new(0., inv_phi, 1.),
-            Vec3::new(0., -inv_phi, -1.),
-            Vec3::new(-1., 0., inv_phi),
-            Vec3::new(0., -inv_phi, -1.),
-            Vec3::new( 1.,  0., -inv_phi),
-            Vec3::new(1., 0., inv_phi),
-            Vec3::new(-1.,  0., -inv_phi),
-            Vec3::new(inv_phi, -1., 0.),
-            Vec3::new(-inv_phi, -1., 0.),
-        ];
-
+        let (vertex_positions, faces) = icosahedron();
         let vertices = vertex_positions
             .iter()
             .map(|&v| V::from_position_normal(v, v.normalize()))
             .collect();
-
-        #[rustfmt::skip]
-            let faces = vec![
-            [ 2,  1,  0],
-            [ 1,  2,  3],
-            [ 5,  4,  3],
-            [ 4,  8,  3],
-            [ 7,  6,  0],
-            [ 6,  9,  0],
-            [11, 10,  4],
-            [10, 11,  6],
-            [ 9,  5,  2],
-            [ 5,  9, 11],
-            [ 8,  7,  1],
-            [ 7,  8, 10],
-            [ 2,  5,  3],
-            [ 8,  1,  3],
-            [ 9,  2,  0],
-            [ 1,  7,  0],
-            [11,  9,  6],
-            [ 7, 10,  6],
-            [ 5, 11,  4],
-            [10,  8,  4],
-        ];
-
         Self::new("Icosahedron".to_string(), faces, vertices)
     }
-
-    pub fn new_icosphere(_num_subdivisions: u32) -> Self {
-        todo!()
-    }
 }
 
 impl<V: FromPositionNormalTextureCoordinates + Position> Mesh<V> {
@@ -196,19 +222,34 @@ impl<V: FromPositionNormalTextureCoordinates + Position> Mesh<V> {
         Mesh::new_cylinder(32, 1, centered)
     }
 
-    // ported from https://vorg.github.io/pex/docs/pex-gen/Cylinder.html
-    // Copyright (c) 2012-2014 Marcin Ignac
     pub fn new_cylinder(num_sides: usize, num_segments: usize, centered: bool) -> Self {
         let radius: f32 = 0.5;
         let height: f32 = 1.0;
+        Self {
+            name: format!(
+                "Cylinder (r={}, h={}, centered={})",
+                radius, height, centered
+            ),
+            ..Self::new_truncated_cone(num_sides, num_segments, radius, radius, centered)
+        }
+    }
 
-        // => radius for top & bottom cap
-        let r_top = radius;
-        let r_bottom = radius;
+    /// Creates a cone of height 1 along the y axis, whose bottom and top caps have the given radii.
+    /// A cap with radius 0 is omitted.
+    // ported from https://vorg.github.io/pex/docs/pex-gen/Cylinder.html
+    // Copyright (c) 2012-2014 Marcin Ignac
+    pub fn new_truncated_cone(
+        num_sides: usize,
+        num_segments: usize,
+        r_bottom: f32,
+        r_top: f32,
+        centered: bool,
+    ) -> Self {
+        let height: f32 = 1.0;
 
         // => generate top & bottom cap
-        let bottom_cap = true;
-        let top_cap = true;
+        let bottom_cap = r_bottom > 0.;
+        let top_cap = r_top > 0.;
 
         let mut faces = Vec::new();
         let mut vertices = Vec::new();
@@ -225,9 +266,15 @@ impl<V: FromPositionNormalTextureCoordinates + Position> Mesh<V> {
                 let y = offset_y + height * segment_ratio;
                 let x = r * f32::cos(side_ratio * TAU);
                 let z = r * f32::sin(side_ratio * TAU);
+                // the side's normal leans towards the narrower cap
                 vertices.push(V::from_position_normal_texture_coordinates(
                     Vec3::new(x, y, z),
-                    Vec3::new(x, 0.0, z),
+                    Vec3::new(
+                        f32::cos(side_ratio * TAU),
+                        (r_bottom - r_top) / height,
+                        f32::sin(side_ratio * TAU),
+                    )
+                    .normalize(),
                     Vec2::new(side_ratio, segment_ratio),
                 ));
                 if i < num_sides && j < num_segments {
@@ -246,7 +293,7 @@ impl<V: FromPositionNormalTextureCoordinates + Position> Mesh<V> {
             vertices.push(V::from_position_normal_texture_coordinates(
                 Vec3::new(0.0, offset_y, 0.0),
                 Vec3::new(0.0, -1.0, 0.0),
-                Vec2::new(0.0, 0.0),
+                Vec2::new(0.5, 0.5),
             ));
             let center_index = index;
             index += 1;
@@ -257,7 +304,7 @@ impl<V: FromPositionNormalTextureCoordinates + Position> Mesh<V> {
                 vertices.push(V::from_position_normal_texture_coordinates(
                     Vec3::new(x, y, z),
                     Vec3::new(0.0, -1.0, 0.0),
-                    Vec2::new(0.0, 0.0),
+                    Vec2::new(0.5, 0.5) + Vec2::new(x, z) * 0.5 / r_bottom,
                 ));
                 if i < num_sides {
                     faces.push([index, index + 1, center_index]);
@@ -270,7 +317,7 @@ impl<V: FromPositionNormalTextureCoordinates + Position> Mesh<V> {
             vertices.push(V::from_position_normal_texture_coordinates(
                 Vec3::new(0.0, offset_y + height, 0.0),
                 Vec3::new(0.0, 1.0, 0.0),
-                Vec2::new(0.0, 0.0),
+                Vec2::new(0.5, 0.5),
             ));
             let center_index = index;
             index += 1;
@@ -281,7 +328,7 @@ impl<V: FromPositionNormalTextureCoordinates + Position> Mesh<V> {
                 vertices.push(V::from_position_normal_texture_coordinates(
                     Vec3::new(x, y, z),
                     Vec3::new(0.0, 1.0, 0.0),
-                    Vec2::new(1.0, 1.0),
+                    Vec2::new(0.5, 0.5) + Vec2::new(x, z) * 0.5 / r_top,
                 ));
                 if i < num_sides {
                     faces.push([index + 1, index, center_index]);
@@ -290,13 +337,190 @@ impl<V: FromPositionNormalTextureCoordinates + Position> Mesh<V> {
             }
         }
 
+        remove_degenerate_faces(&mut faces, &vertices);
+
         Self::new(
             format!(
-                "Cylinder (r={}, h={}, centered={})",
-                radius, height, centered
+                "Truncated cone (r_bottom={}, r_top={}, h={}, centered={})",
+                r_bottom, r_top, height, centered
             ),
             faces,
             vertices,
         )
     }
+
+    /// Creates a cone of height 1 along the y axis, whose base has a radius of 0.5.
+    pub fn new_cone(num_sides: usize, num_segments: usize, centered: bool) -> Self {
+        Self {
+            name: format!("Cone (r=0.5, h=1, centered={})", centered),
+            ..Self::new_truncated_cone(num_sides, num_segments, 0.5, 0., centered)
+        }
+    }
+
+    /// Creates a sphere with radius 1 by subdividing each face of an icosahedron into four faces
+    /// the given number of times.
+    pub fn new_icosphere(num_subdivisions: u32) -> Self {
+        let (positions, mut faces) = icosahedron();
+        let mut positions: Vec<Vec3> = positions.iter().map(|p| p.normalize()).collect();
+
+        for _ in 0..num_subdivisions {
+            // each edge is split once, its midpoint is shared by both faces adjacent to it
+            let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+            let mut midpoint = |a: u32, b: u32| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    positions.push((positions[a as usize] + positions[b as usize]).normalize());
+                    positions.len() as u32 - 1
+                })
+            };
+            let mut subdivided = Vec::with_capacity(faces.len() * 4);
+            for [a, b, c] in faces {
+                let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                subdivided.extend([[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]);
+            }
+            faces = subdivided;
+        }
+
+        // texture coordinates are the longitude and latitude of a vertex
+        let mut vertices: Vec<(Vec3, Vec2)> = positions
+            .iter()
+            .map(|&p| {
+                (
+                    p,
+                    Vec2::new(0.5 + p.z.atan2(p.x) / TAU, p.y.clamp(-1., 1.).acos() / PI),
+                )
+            })
+            .collect();
+        // faces crossing the seam at longitude 0 reference copies of their vertices on the seam's
+        // right side, whose texture coordinates continue beyond 1
+        let mut wrapped: HashMap<u32, u32> = HashMap::new();
+        for face in faces.iter_mut() {
+            let u = face.map(|i| vertices[i as usize].1.x);
+            if u.iter().fold(0f32, |a, &b| a.max(b)) - u.iter().fold(1f32, |a, &b| a.min(b)) <= 0.5
+            {
+                continue;
+            }
+            for i in face.iter_mut() {
+                let index = *i;
+                if vertices[index as usize].1.x < 0.5 {
+                    *i = *wrapped.entry(index).or_insert_with(|| {
+                        let (position, texture_coordinates) = vertices[index as usize];
+                        vertices.push((position, texture_coordinates + Vec2::X));
+                        vertices.len() as u32 - 1
+                    });
+                }
+            }
+        }
+
+        let vertices = vertices
+            .iter()
+            .map(|&(p, t)| V::from_position_normal_texture_coordinates(p, p, t))
+            .collect();
+        Self::new(
+            format!("Icosphere (subdivisions={})", num_subdivisions),
+            faces,
+            vertices,
+        )
+    }
+
+    /// Creates a disk with radius 0.5 in the xz plane, which is visible from both sides.
+    pub fn new_disk(num_sides: usize) -> Self {
+        let mut faces = Vec::new();
+        let mut vertices = Vec::new();
+        for normal in [Vec3::Y, -Vec3::Y] {
+            let center = vertices.len() as u32;
+            vertices.push(V::from_position_normal_texture_coordinates(
+                Vec3::ZERO,
+                normal,
+                Vec2::new(0.5, 0.5),
+            ));
+            for i in 0..num_sides + 1 {
+                let angle = i as f32 / num_sides as f32 * TAU;
+                let direction = Vec2::new(f32::cos(angle), f32::sin(angle));
+                vertices.push(V::from_position_normal_texture_coordinates(
+                    Vec3::new(direction.x, 0.0, direction.y) * 0.5,
+                    normal,
+                    Vec2::new(0.5, 0.5) + direction * 0.5,
+                ));
+            }
+            for i in 0..num_sides as u32 {
+                let (a, b) = (center + 1 + i, center + 2 + i);
+                faces.push(if normal == Vec3::Y {
+                    [center, b, a]
+                } else {
+                    [center, a, b]
+                });
+            }
+        }
+        Self::new("Disk (r=0.5)".to_string(), faces, vertices)
+    }
+
+    /// Creates a torus around the y axis, whose tube's center has a distance of `major_radius` to
+    /// the origin and whose tube has a radius of `minor_radius`.
+    pub fn new_torus(
+        num_sides: usize,
+        num_rings: usize,
+        major_radius: f32,
+        minor_radius: f32,
+    ) -> Self {
+        let mut vertices = Vec::new();
+        for j in 0..num_rings + 1 {
+            let ring_ratio = j as f32 / num_rings as f32;
+            let ring_direction =
+                Vec3::new(f32::cos(ring_ratio * TAU), 0.0, f32::sin(ring_ratio * TAU));
+            for i in 0..num_sides + 1 {
+                let side_ratio = i as f32 / num_sides as f32;
+                let normal = ring_direction * f32::cos(side_ratio * TAU)
+                    + Vec3::Y * f32::sin(side_ratio * TAU);
+                vertices.push(V::from_position_normal_texture_coordinates(
+                    ring_direction * major_radius + normal * minor_radius,
+                    normal,
+                    Vec2::new(ring_ratio, side_ratio),
+                ));
+            }
+        }
+        Self::new(
+            format!("Torus (R={}, r={})", major_radius, minor_radius),
+            grid_faces(num_rings, num_sides),
+            vertices,
+        )
+    }
+
+    /// Creates a capsule along the y axis, i.e., a cylinder with the given radius and height
+    /// capped by hemispheres, which is centered at the origin.
+    pub fn new_capsule(num_sides: usize, num_rings: usize, radius: f32, height: f32) -> Self {
+        let total_height = height + 2. * radius;
+        let mut vertices = Vec::new();
+        // each hemisphere has `num_rings + 1` rings from its pole to its equator, the cylinder
+        // spans the gap between both equators
+        for j in 0..2 * (num_rings + 1) {
+            // the lower hemisphere mirrors the upper one, s.t. both poles are exact
+            let (ring, hemisphere) = if j <= num_rings {
+                (j, 1.)
+            } else {
+                (2 * num_rings + 1 - j, -1.)
+            };
+            let latitude = ring as f32 / num_rings as f32 * PI * 0.5;
+            for i in 0..num_sides + 1 {
+                let side_ratio = i as f32 / num_sides as f32;
+                let normal = Vec3::new(
+                    f32::sin(latitude) * f32::cos(side_ratio * TAU),
+                    f32::cos(latitude) * hemisphere,
+                    f32::sin(latitude) * f32::sin(side_ratio * TAU),
+                );
+                let position = Vec3::Y * height * 0.5 * hemisphere + normal * radius;
+                vertices.push(V::from_position_normal_texture_coordinates(
+                    position,
+                    normal,
+                    Vec2::new(side_ratio, 0.5 - position.y / total_height),
+                ));
+            }
+        }
+        let mut faces = grid_faces(2 * num_rings + 1, num_sides);
+        remove_degenerate_faces(&mut faces, &vertices);
+        Self::new(
+            format!("Capsule (r={}, h={})", radius, height),
+            faces,
+            vertices,
+        )
+    }
 }
@@ -1,16 +1,23 @@
 extern crate core;
 
+#[cfg(target_arch = "wasm32")]
 use std::collections::HashMap;
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
 pub mod framework;
 pub mod lindenmayer;
 pub mod lsystemrenderer;
 
+#[cfg(target_arch = "wasm32")]
 use crate::framework::app::AppRunner;
+#[cfg(target_arch = "wasm32")]
 use crate::framework::util::window::WindowConfig;
-use crate::lindenmayer::LSystem;
+#[cfg(target_arch = "wasm32")]
+use crate::lindenmayer::{parser::parse_l_system, LSystem, LSystemDefinition};
+#[cfg(target_arch = "wasm32")]
 use crate::lsystemrenderer::scene_descriptor::LSystemSceneDescriptor;
+#[cfg(target_arch = "wasm32")]
 use crate::lsystemrenderer::App;
 
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
@@ -37,6 +44,48 @@ pub fn main(canvas_id: String, scene: JsValue, l_system_definitions: JsValue) {
     wasm_bindgen_futures::spawn_local(run(canvas_id, scene_descriptor, l_systems));
 }
 
+/// Parses the given L-system definition and returns `null` if it is valid or the `ParseError`
+/// describing the first error in the definition otherwise.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen()]
+pub fn check_l_system_definition(definition: JsValue) -> JsValue {
+    let definition: LSystemDefinition = match serde_wasm_bindgen::from_value(definition) {
+        Ok(definition) => definition,
+        Err(error) => return JsValue::from_str(&error.to_string()),
+    };
+    match parse_l_system(&definition) {
+        Ok(_) => JsValue::NULL,
+        Err(error) => serde_wasm_bindgen::to_value(&error).unwrap_or(JsValue::NULL),
+    }
+}
+
+/// Derives the given number of iterations of the given L-system definition with the given seed
+/// (or a random one if it is `undefined`) and returns the module strings of the axiom and each
+/// iteration.
+/// Throws a message describing the error if the definition is invalid or a derivation step fails.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen()]
+pub fn derive_l_system_definition(
+    definition: JsValue,
+    iterations: usize,
+    seed: JsValue,
+) -> Result<JsValue, JsValue> {
+    let definition: LSystemDefinition = serde_wasm_bindgen::from_value(definition)?;
+    let mut l_system =
+        LSystem::new(&definition).map_err(|error| JsValue::from_str(&error.to_string()))?;
+    if let Some(seed) = serde_wasm_bindgen::from_value::<Option<u64>>(seed)? {
+        l_system.set_seed(seed);
+    }
+    let mut modules = vec![l_system.current()];
+    for _ in 0..iterations {
+        l_system
+            .next_modules()
+            .map_err(|error| JsValue::from_str(&error.to_string()))?;
+        modules.push(l_system.current());
+    }
+    Ok(serde_wasm_bindgen::to_value(&modules)?)
+}
+
 #[cfg(target_arch = "wasm32")]
 async fn run(
     canvas_id: String,
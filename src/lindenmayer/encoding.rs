@@ -0,0 +1,167 @@
+/// Writes values into a compact little-endian binary blob.
+#[derive(Clone, Debug, Default)]
+pub struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    pub fn u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    pub fn f32(&mut self, value: f32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    /// Writes `None` as `u64::MAX`.
+    pub fn optional_u64(&mut self, value: Option<u64>) {
+        self.u64(value.unwrap_or(u64::MAX));
+    }
+    pub fn str(&mut self, value: &str) {
+        self.u64(value.len() as u64);
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+    /// Writes the length of the slice followed by its raw bytes.
+    pub fn slice<T: bytemuck::Pod>(&mut self, values: &[T]) {
+        self.u64(values.len() as u64);
+        self.bytes.extend_from_slice(bytemuck::cast_slice(values));
+    }
+}
+
+/// Reads values written by an `Encoder`.
+/// All methods return `None` if the blob ends prematurely.
+#[derive(Clone, Debug)]
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.bytes.len() {
+            return None;
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Some(taken)
+    }
+
+    pub fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+    pub fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+    pub fn f32(&mut self) -> Option<f32> {
+        Some(f32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+    pub fn optional_u64(&mut self) -> Option<Option<u64>> {
+        let value = self.u64()?;
+        Some((value != u64::MAX).then_some(value))
+    }
+    pub fn usize(&mut self) -> Option<usize> {
+        usize::try_from(self.u64()?).ok()
+    }
+    pub fn str(&mut self) -> Option<&'a str> {
+        let len = self.usize()?;
+        std::str::from_utf8(self.take(len)?).ok()
+    }
+    pub fn slice<T: bytemuck::Pod>(&mut self) -> Option<Vec<T>> {
+        let len = self.usize()?;
+        let bytes = self.take(len.checked_mul(std::mem::size_of::<T>())?)?;
+        // the blob is not necessarily aligned for `T`
+        let mut values = vec![T::zeroed(); len];
+        bytemuck::cast_slice_mut(&mut values).copy_from_slice(bytes);
+        Some(values)
+    }
+}
+
+/// A hash that is stable across platforms and builds, e.g., to key persisted data (64 bit FNV-1a).
+pub fn stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode() -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        encoder.u32(7);
+        encoder.u64(u64::MAX - 1);
+        encoder.f32(-0.5);
+        encoder.optional_u64(None);
+        encoder.optional_u64(Some(3));
+        encoder.str("F(x)");
+        encoder.slice(&[[1u32, 2], [3, 4]]);
+        encoder.into_bytes()
+    }
+
+    type Values<'a> = (
+        u32,
+        u64,
+        f32,
+        Option<u64>,
+        Option<u64>,
+        &'a str,
+        Vec<[u32; 2]>,
+    );
+
+    fn decode(bytes: &[u8]) -> Option<Values<'_>> {
+        let mut decoder = Decoder::new(bytes);
+        Some((
+            decoder.u32()?,
+            decoder.u64()?,
+            decoder.f32()?,
+            decoder.optional_u64()?,
+            decoder.optional_u64()?,
+            decoder.str()?,
+            decoder.slice()?,
+        ))
+    }
+
+    #[test]
+    fn decodes_encoded_values() {
+        assert_eq!(
+            decode(&encode()),
+            Some((
+                7,
+                u64::MAX - 1,
+                -0.5,
+                None,
+                Some(3),
+                "F(x)",
+                vec![[1, 2], [3, 4]]
+            ))
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_and_corrupted_blobs() {
+        let bytes = encode();
+        for len in 0..bytes.len() {
+            assert_eq!(decode(&bytes[..len]), None);
+        }
+        // a slice length that overflows the number of bytes
+        let mut encoder = Encoder::new();
+        encoder.u64(u64::MAX / 2);
+        assert_eq!(Decoder::new(&encoder.into_bytes()).slice::<u64>(), None);
+        // a string that isn't valid UTF-8
+        let mut encoder = Encoder::new();
+        encoder.slice(&[0xffu8, 0xfe]);
+        assert_eq!(Decoder::new(&encoder.into_bytes()).str(), None);
+    }
+}
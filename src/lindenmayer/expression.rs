@@ -0,0 +1,795 @@
+use crate::lindenmayer::random::Random;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum UnaryOperator {
+    Negate,
+    Not,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Power,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    And,
+    Or,
+}
+
+impl BinaryOperator {
+    fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "+" => Some(Self::Add),
+            "-" => Some(Self::Subtract),
+            "*" => Some(Self::Multiply),
+            "/" => Some(Self::Divide),
+            "%" => Some(Self::Remainder),
+            "**" => Some(Self::Power),
+            "<" => Some(Self::Less),
+            "<=" => Some(Self::LessEqual),
+            ">" => Some(Self::Greater),
+            ">=" => Some(Self::GreaterEqual),
+            "==" | "===" => Some(Self::Equal),
+            "!=" | "!==" => Some(Self::NotEqual),
+            "&&" => Some(Self::And),
+            "||" => Some(Self::Or),
+            _ => None,
+        }
+    }
+
+    // binding power of the operator, higher values bind stronger
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Or => 1,
+            Self::And => 2,
+            Self::Equal | Self::NotEqual => 3,
+            Self::Less | Self::LessEqual | Self::Greater | Self::GreaterEqual => 4,
+            Self::Add | Self::Subtract => 5,
+            Self::Multiply | Self::Divide | Self::Remainder => 6,
+            Self::Power => 7,
+        }
+    }
+
+    fn is_right_associative(&self) -> bool {
+        matches!(self, Self::Power)
+    }
+}
+
+/// Functions of JavaScript's `Math` object that may be used in expressions.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Function {
+    Abs,
+    Acos,
+    Asin,
+    Atan,
+    Atan2,
+    Cbrt,
+    Ceil,
+    Cos,
+    Exp,
+    Floor,
+    Hypot,
+    Log,
+    Log10,
+    Log2,
+    Max,
+    Min,
+    Pow,
+    Random,
+    Round,
+    Sign,
+    Sin,
+    Sqrt,
+    Tan,
+    Trunc,
+}
+
+const FUNCTIONS: [(&str, Function); 24] = [
+    ("abs", Function::Abs),
+    ("acos", Function::Acos),
+    ("asin", Function::Asin),
+    ("atan", Function::Atan),
+    ("atan2", Function::Atan2),
+    ("cbrt", Function::Cbrt),
+    ("ceil", Function::Ceil),
+    ("cos", Function::Cos),
+    ("exp", Function::Exp),
+    ("floor", Function::Floor),
+    ("hypot", Function::Hypot),
+    ("log", Function::Log),
+    ("log10", Function::Log10),
+    ("log2", Function::Log2),
+    ("max", Function::Max),
+    ("min", Function::Min),
+    ("pow", Function::Pow),
+    ("random", Function::Random),
+    ("round", Function::Round),
+    ("sign", Function::Sign),
+    ("sin", Function::Sin),
+    ("sqrt", Function::Sqrt),
+    ("tan", Function::Tan),
+    ("trunc", Function::Trunc),
+];
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        FUNCTIONS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, function)| *function)
+    }
+
+    pub fn name(&self) -> &'static str {
+        FUNCTIONS
+            .iter()
+            .find(|(_, function)| function == self)
+            .map(|(name, _)| *name)
+            .unwrap()
+    }
+
+    /// The minimum and maximum number of arguments the function accepts.
+    /// Variadic functions have no maximum.
+    pub fn arity(&self) -> (usize, Option<usize>) {
+        match self {
+            Self::Random => (0, Some(0)),
+            Self::Atan2 | Self::Pow => (2, Some(2)),
+            Self::Hypot | Self::Max | Self::Min => (0, None),
+            _ => (1, Some(1)),
+        }
+    }
+
+    fn constant_from_name(name: &str) -> Option<f32> {
+        match name {
+            "PI" => Some(std::f32::consts::PI),
+            "E" => Some(std::f32::consts::E),
+            _ => None,
+        }
+    }
+}
+
+/// Errors that can occur when evaluating an expression.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EvaluationError {
+    DivisionByZero,
+}
+
+impl Display for EvaluationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvaluationError::DivisionByZero => write!(f, "Division by zero"),
+        }
+    }
+}
+
+/// An arithmetic expression used in a production's condition or in the parameters of its
+/// successor modules.
+/// Parameters are resolved to indices into the list of parameters bound by the production's
+/// module declaration, and global L-system parameters are inlined as constants.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expression {
+    Number(f32),
+    Parameter(usize),
+    Unary(UnaryOperator, Box<Expression>),
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+    Call(Function, Vec<Expression>),
+}
+
+fn is_truthy(value: f32) -> bool {
+    value != 0. && !value.is_nan()
+}
+
+fn from_bool(value: bool) -> f32 {
+    if value {
+        1.
+    } else {
+        0.
+    }
+}
+
+impl Expression {
+    pub fn parse(
+        source: &str,
+        parameters: &[String],
+        globals: &HashMap<String, f32>,
+    ) -> Result<Self, ExpressionError> {
+        let tokens = tokenize(source)?;
+        let mut parser = ExpressionParser {
+            tokens,
+            position: 0,
+            end: source.chars().count(),
+            parameters,
+            globals,
+        };
+        let expression = parser.parse_expression(0)?;
+        if let Some(token) = parser.peek() {
+            Err(ExpressionError::unexpected_token(token))
+        } else {
+            Ok(expression)
+        }
+    }
+
+    pub fn evaluate(
+        &self,
+        parameters: &[f32],
+        random: &mut Random,
+    ) -> Result<f32, EvaluationError> {
+        let value = match self {
+            Expression::Number(value) => *value,
+            Expression::Parameter(index) => parameters[*index],
+            Expression::Unary(operator, operand) => {
+                let value = operand.evaluate(parameters, random)?;
+                match operator {
+                    UnaryOperator::Negate => -value,
+                    UnaryOperator::Not => from_bool(!is_truthy(value)),
+                }
+            }
+            Expression::Binary(operator, lhs, rhs) => {
+                let left = lhs.evaluate(parameters, random)?;
+                // && and || short circuit and evaluate to one of their operands, just like in JS
+                match operator {
+                    BinaryOperator::And => {
+                        return if is_truthy(left) {
+                            rhs.evaluate(parameters, random)
+                        } else {
+                            Ok(left)
+                        };
+                    }
+                    BinaryOperator::Or => {
+                        return if is_truthy(left) {
+                            Ok(left)
+                        } else {
+                            rhs.evaluate(parameters, random)
+                        };
+                    }
+                    _ => {}
+                }
+                let right = rhs.evaluate(parameters, random)?;
+                match operator {
+                    BinaryOperator::Add => left + right,
+                    BinaryOperator::Subtract => left - right,
+                    BinaryOperator::Multiply => left * right,
+                    BinaryOperator::Divide | BinaryOperator::Remainder if right == 0. => {
+                        return Err(EvaluationError::DivisionByZero);
+                    }
+                    BinaryOperator::Divide => left / right,
+                    BinaryOperator::Remainder => left % right,
+                    BinaryOperator::Power => left.powf(right),
+                    BinaryOperator::Less => from_bool(left < right),
+                    BinaryOperator::LessEqual => from_bool(left <= right),
+                    BinaryOperator::Greater => from_bool(left > right),
+                    BinaryOperator::GreaterEqual => from_bool(left >= right),
+                    BinaryOperator::Equal => from_bool(left == right),
+                    BinaryOperator::NotEqual => from_bool(left != right),
+                    BinaryOperator::And | BinaryOperator::Or => unreachable!(),
+                }
+            }
+            Expression::Call(function, arguments) => {
+                // variadic functions fold their arguments instead of collecting them
+                let mut fold = |init: f32, f: fn(f32, f32) -> f32| {
+                    arguments.iter().try_fold(init, |folded, a| {
+                        a.evaluate(parameters, random).map(|value| f(folded, value))
+                    })
+                };
+                match function {
+                    Function::Hypot => fold(0., |sum, v| sum + v * v)?.sqrt(),
+                    Function::Max => fold(f32::NEG_INFINITY, f32::max)?,
+                    Function::Min => fold(f32::INFINITY, f32::min)?,
+                    Function::Random => random.next_f32(),
+                    _ => {
+                        // the number of arguments has been checked when the expression was parsed
+                        let mut arg = |i: usize| match arguments.get(i) {
+                            Some(argument) => argument.evaluate(parameters, random),
+                            None => Ok(f32::NAN),
+                        };
+                        let (x, y) = (arg(0)?, arg(1)?);
+                        match function {
+                            Function::Abs => x.abs(),
+                            Function::Acos => x.acos(),
+                            Function::Asin => x.asin(),
+                            Function::Atan => x.atan(),
+                            Function::Atan2 => x.atan2(y),
+                            Function::Cbrt => x.cbrt(),
+                            Function::Ceil => x.ceil(),
+                            Function::Cos => x.cos(),
+                            Function::Exp => x.exp(),
+                            Function::Floor => x.floor(),
+                            Function::Log => x.ln(),
+                            Function::Log10 => x.log10(),
+                            Function::Log2 => x.log2(),
+                            Function::Pow => x.powf(y),
+                            Function::Round => (x + 0.5).floor(),
+                            Function::Sign => {
+                                if x == 0. || x.is_nan() {
+                                    x
+                                } else {
+                                    x.signum()
+                                }
+                            }
+                            Function::Sin => x.sin(),
+                            Function::Sqrt => x.sqrt(),
+                            Function::Tan => x.tan(),
+                            Function::Trunc => x.trunc(),
+                            Function::Hypot | Function::Max | Function::Min | Function::Random => {
+                                unreachable!()
+                            }
+                        }
+                    }
+                }
+            }
+        };
+        Ok(value)
+    }
+
+    pub fn evaluate_condition(
+        &self,
+        parameters: &[f32],
+        random: &mut Random,
+    ) -> Result<bool, EvaluationError> {
+        self.evaluate(parameters, random).map(is_truthy)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum TokenKind {
+    Number(f32),
+    Identifier,
+    Operator,
+    LeftParenthesis,
+    RightParenthesis,
+    Comma,
+    Dot,
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    kind: TokenKind,
+    text: String,
+    position: usize,
+}
+
+/// The kinds of errors that can occur when parsing an expression.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExpressionErrorKind {
+    InvalidNumber(String),
+    UnexpectedCharacter(char),
+    UnexpectedToken(String),
+    UnexpectedEnd,
+    UnknownIdentifier(String),
+    UnknownFunction(String),
+    UnaryBeforeExponentiation,
+    WrongArity {
+        function: Function,
+        arguments: usize,
+    },
+}
+
+impl Display for ExpressionErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidNumber(number) => write!(f, "Invalid number '{}'", number),
+            Self::UnexpectedCharacter(c) => write!(f, "Unexpected character '{}'", c),
+            Self::UnexpectedToken(token) => write!(f, "Unexpected token '{}'", token),
+            Self::UnexpectedEnd => write!(f, "Unexpected end of expression"),
+            Self::UnknownIdentifier(identifier) => {
+                write!(f, "Unknown identifier '{}'", identifier)
+            }
+            Self::UnknownFunction(name) => write!(f, "Unknown function 'Math.{}'", name),
+            Self::UnaryBeforeExponentiation => write!(
+                f,
+                "Unary operator before '**', parentheses must be used to disambiguate"
+            ),
+            Self::WrongArity {
+                function,
+                arguments,
+            } => {
+                let (min, max) = function.arity();
+                let expected = match max {
+                    Some(max) if max == min => format!("{}", min),
+                    Some(max) => format!("{} to {}", min, max),
+                    None => format!("at least {}", min),
+                };
+                write!(
+                    f,
+                    "Function 'Math.{}' expects {} argument(s) but got {}",
+                    function.name(),
+                    expected,
+                    arguments
+                )
+            }
+        }
+    }
+}
+
+/// An error in an expression's source.
+/// The position is the index of the character at which the error was detected.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExpressionError {
+    position: usize,
+    kind: ExpressionErrorKind,
+}
+
+impl ExpressionError {
+    fn new(position: usize, kind: ExpressionErrorKind) -> Self {
+        Self { position, kind }
+    }
+    fn unexpected_token(token: &Token) -> Self {
+        Self::new(
+            token.position,
+            ExpressionErrorKind::UnexpectedToken(token.text.clone()),
+        )
+    }
+    pub fn position(&self) -> usize {
+        self.position
+    }
+    pub fn kind(&self) -> &ExpressionErrorKind {
+        &self.kind
+    }
+}
+
+impl Display for ExpressionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at position {}", self.kind, self.position)
+    }
+}
+
+const OPERATORS: [&str; 20] = [
+    "===", "!==", "**", "<=", ">=", "==", "!=", "&&", "||", "+", "-", "*", "/", "%", "<", ">", "!",
+    "(", ")", ",",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, ExpressionError> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = source.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit()))
+        {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // exponent, e.g. 1e-3
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = text.parse::<f32>().map_err(|_| {
+                ExpressionError::new(start, ExpressionErrorKind::InvalidNumber(text.clone()))
+            })?;
+            tokens.push(Token {
+                kind: TokenKind::Number(value),
+                text,
+                position: start,
+            });
+        } else if c.is_alphabetic() || c == '_' || c == '$' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$')
+            {
+                i += 1;
+            }
+            tokens.push(Token {
+                kind: TokenKind::Identifier,
+                text: chars[start..i].iter().collect(),
+                position: start,
+            });
+        } else if c == '.' {
+            tokens.push(Token {
+                kind: TokenKind::Dot,
+                text: ".".to_string(),
+                position: i,
+            });
+            i += 1;
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 3)].iter().collect();
+            let operator = OPERATORS
+                .iter()
+                .find(|o| rest.starts_with(*o))
+                .ok_or_else(|| {
+                    ExpressionError::new(i, ExpressionErrorKind::UnexpectedCharacter(c))
+                })?;
+            let kind = match *operator {
+                "(" => TokenKind::LeftParenthesis,
+                ")" => TokenKind::RightParenthesis,
+                "," => TokenKind::Comma,
+                _ => TokenKind::Operator,
+            };
+            tokens.push(Token {
+                kind,
+                text: operator.to_string(),
+                position: i,
+            });
+            i += operator.len();
+        }
+    }
+    Ok(tokens)
+}
+
+struct ExpressionParser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    // the number of characters in the expression's source
+    end: usize,
+    parameters: &'a [String],
+    globals: &'a HashMap<String, f32>,
+}
+
+impl<'a> ExpressionParser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<Token, ExpressionError> {
+        let token =
+            self.tokens.get(self.position).cloned().ok_or_else(|| {
+                ExpressionError::new(self.end, ExpressionErrorKind::UnexpectedEnd)
+            })?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, kind: TokenKind) -> Result<Token, ExpressionError> {
+        let token = self.next()?;
+        if token.kind == kind {
+            Ok(token)
+        } else {
+            Err(ExpressionError::unexpected_token(&token))
+        }
+    }
+
+    // precedence climbing, see https://eli.thegreenplace.net/2012/08/02/parsing-expressions-by-precedence-climbing
+    fn parse_expression(&mut self, min_precedence: u8) -> Result<Expression, ExpressionError> {
+        // like in JavaScript, the base of an exponentiation can't be a unary expression, e.g.,
+        // `-x ** 2` is an error while `(-x) ** 2` and `-(x ** 2)` are not
+        let mut unary = self.peek().is_some_and(|t| t.kind == TokenKind::Operator);
+        let mut lhs = self.parse_unary()?;
+        while let Some(token) = self.peek() {
+            let operator = match (&token.kind, BinaryOperator::from_token(&token.text)) {
+                (TokenKind::Operator, Some(operator)) => operator,
+                _ => break,
+            };
+            if operator.precedence() < min_precedence {
+                break;
+            }
+            if unary && operator == BinaryOperator::Power {
+                return Err(ExpressionError::new(
+                    token.position,
+                    ExpressionErrorKind::UnaryBeforeExponentiation,
+                ));
+            }
+            unary = false;
+            self.position += 1;
+            let next_min_precedence = if operator.is_right_associative() {
+                operator.precedence()
+            } else {
+                operator.precedence() + 1
+            };
+            let rhs = self.parse_expression(next_min_precedence)?;
+            lhs = Expression::Binary(operator, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expression, ExpressionError> {
+        if let Some(token) = self.peek() {
+            if token.kind == TokenKind::Operator {
+                let operator = match token.text.as_str() {
+                    "-" => Some(UnaryOperator::Negate),
+                    "!" => Some(UnaryOperator::Not),
+                    "+" => None,
+                    _ => return Err(ExpressionError::unexpected_token(token)),
+                };
+                self.position += 1;
+                let operand = self.parse_unary()?;
+                return Ok(match operator {
+                    Some(operator) => Expression::Unary(operator, Box::new(operand)),
+                    None => operand,
+                });
+            }
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expression, ExpressionError> {
+        let token = self.next()?;
+        match token.kind {
+            TokenKind::Number(value) => Ok(Expression::Number(value)),
+            TokenKind::LeftParenthesis => {
+                let expression = self.parse_expression(0)?;
+                self.expect(TokenKind::RightParenthesis)?;
+                Ok(expression)
+            }
+            TokenKind::Identifier => {
+                if token.text == "Math" {
+                    self.parse_math_member()
+                } else if let Some(index) = self.parameters.iter().position(|p| *p == token.text) {
+                    Ok(Expression::Parameter(index))
+                } else if let Some(value) = self.globals.get(&token.text) {
+                    Ok(Expression::Number(*value))
+                } else {
+                    match token.text.as_str() {
+                        "true" => Ok(Expression::Number(1.)),
+                        "false" => Ok(Expression::Number(0.)),
+                        _ => Err(ExpressionError::new(
+                            token.position,
+                            ExpressionErrorKind::UnknownIdentifier(token.text),
+                        )),
+                    }
+                }
+            }
+            _ => Err(ExpressionError::unexpected_token(&token)),
+        }
+    }
+
+    fn parse_math_member(&mut self) -> Result<Expression, ExpressionError> {
+        self.expect(TokenKind::Dot)?;
+        let member = self.expect(TokenKind::Identifier)?;
+        if let Some(value) = Function::constant_from_name(&member.text) {
+            return Ok(Expression::Number(value));
+        }
+        let function = Function::from_name(&member.text).ok_or_else(|| {
+            ExpressionError::new(
+                member.position,
+                ExpressionErrorKind::UnknownFunction(member.text.clone()),
+            )
+        })?;
+        self.expect(TokenKind::LeftParenthesis)?;
+        let mut arguments = Vec::new();
+        if self.peek().map(|t| &t.kind) == Some(&TokenKind::RightParenthesis) {
+            self.position += 1;
+        } else {
+            loop {
+                arguments.push(self.parse_expression(0)?);
+                let token = self.next()?;
+                match token.kind {
+                    TokenKind::Comma => continue,
+                    TokenKind::RightParenthesis => break,
+                    _ => return Err(ExpressionError::unexpected_token(&token)),
+                }
+            }
+        }
+        let (min, max) = function.arity();
+        if arguments.len() < min || max.is_some_and(|max| arguments.len() > max) {
+            return Err(ExpressionError::new(
+                member.position,
+                ExpressionErrorKind::WrongArity {
+                    function,
+                    arguments: arguments.len(),
+                },
+            ));
+        }
+        Ok(Expression::Call(function, arguments))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(source: &str, parameters: &[f32]) -> Result<f32, EvaluationError> {
+        let names = ["x".to_string(), "y".to_string()];
+        let globals = HashMap::from([("g".to_string(), 10.)]);
+        Expression::parse(source, &names[..parameters.len()], &globals)
+            .unwrap()
+            .evaluate(parameters, &mut Random::new(0))
+    }
+
+    fn parse_error(source: &str) -> ExpressionError {
+        Expression::parse(source, &["x".to_string()], &HashMap::new()).unwrap_err()
+    }
+
+    #[test]
+    fn respects_precedence_and_associativity() {
+        assert_eq!(evaluate("1 + 2 * 3", &[]), Ok(7.));
+        assert_eq!(evaluate("(1 + 2) * 3", &[]), Ok(9.));
+        assert_eq!(evaluate("2 ** 3 ** 2", &[]), Ok(512.));
+        assert_eq!(evaluate("10 - 4 - 3", &[]), Ok(3.));
+        assert_eq!(evaluate("(-x) ** 2", &[3.]), Ok(9.));
+        assert_eq!(evaluate("-(x ** 2)", &[3.]), Ok(-9.));
+        assert_eq!(evaluate("2 ** -x", &[1.]), Ok(0.5));
+        assert_eq!(evaluate("7 % 4 + x * g", &[2.]), Ok(23.));
+    }
+
+    #[test]
+    fn evaluates_logic_like_javascript() {
+        assert_eq!(evaluate("x > 1 && y <= 2", &[2., 2.]), Ok(1.));
+        assert_eq!(evaluate("x == 1 || !y", &[0., 0.]), Ok(1.));
+        // && and || evaluate to one of their operands
+        assert_eq!(evaluate("x && y", &[3., 4.]), Ok(4.));
+        assert_eq!(evaluate("x || y", &[0., 4.]), Ok(4.));
+        assert_eq!(evaluate("x === 1 && x !== 2", &[1.]), Ok(1.));
+    }
+
+    #[test]
+    fn evaluates_math_functions() {
+        assert_eq!(evaluate("Math.max(x, y, 3)", &[1., 2.]), Ok(3.));
+        assert_eq!(evaluate("Math.min()", &[]), Ok(f32::INFINITY));
+        assert_eq!(evaluate("Math.hypot(3, 4)", &[]), Ok(5.));
+        assert_eq!(evaluate("Math.pow(2, 10)", &[]), Ok(1024.));
+        assert_eq!(evaluate("Math.round(-2.5)", &[]), Ok(-2.));
+        assert_eq!(evaluate("Math.sign(-x)", &[3.]), Ok(-1.));
+        assert_eq!(evaluate("Math.floor(Math.PI)", &[]), Ok(3.));
+        let random = evaluate("Math.random()", &[]).unwrap();
+        assert!((0. ..1.).contains(&random));
+    }
+
+    #[test]
+    fn reports_division_by_zero() {
+        assert_eq!(
+            evaluate("1 / (x - 1)", &[1.]),
+            Err(EvaluationError::DivisionByZero)
+        );
+        assert_eq!(
+            evaluate("x % 0", &[1.]),
+            Err(EvaluationError::DivisionByZero)
+        );
+        // the right operand of && is not evaluated if the left one is falsy
+        assert_eq!(evaluate("x != 0 && 1 / x", &[0.]), Ok(0.));
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        let error = parse_error("x + z");
+        assert_eq!(error.position(), 4);
+        assert_eq!(
+            error.kind(),
+            &ExpressionErrorKind::UnknownIdentifier("z".to_string())
+        );
+
+        let error = parse_error("Math.pow(x)");
+        assert_eq!(
+            error.kind(),
+            &ExpressionErrorKind::WrongArity {
+                function: Function::Pow,
+                arguments: 1
+            }
+        );
+
+        assert_eq!(
+            parse_error("Math.eval(x)").kind(),
+            &ExpressionErrorKind::UnknownFunction("eval".to_string())
+        );
+        assert_eq!(
+            parse_error("x +").kind(),
+            &ExpressionErrorKind::UnexpectedEnd
+        );
+        assert_eq!(
+            parse_error("x # 1").kind(),
+            &ExpressionErrorKind::UnexpectedCharacter('#')
+        );
+        assert_eq!(parse_error("(x").position(), 2);
+
+        // JavaScript rejects unary operators before `**` instead of picking a precedence
+        for source in ["-x ** 2", "2 * !x ** 2", "+x ** 2"] {
+            let error = parse_error(source);
+            assert_eq!(
+                error.kind(),
+                &ExpressionErrorKind::UnaryBeforeExponentiation
+            );
+            assert_eq!(error.position(), source.find("**").unwrap());
+        }
+    }
+}
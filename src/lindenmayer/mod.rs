@@ -1,22 +1,26 @@
+use crate::lindenmayer::expression::EvaluationError;
+use crate::lindenmayer::module_string::{ModuleRef, ModuleString};
+use crate::lindenmayer::parser::{parse_l_system, ParseError, ProductionTable};
+use crate::lindenmayer::production::Production;
+use crate::lindenmayer::query::is_query_module;
+use crate::lindenmayer::random::Random;
+use crate::lindenmayer::timed::{AppliedProduction, ModuleHistory, TimedSnapshot};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen(module = "/js/l-system/l-system.js")]
-extern "C" {
-    type LSystemIterator;
-
-    #[wasm_bindgen(static_method_of = LSystemIterator, js_name = "createLSystemIterator")]
-    fn create_l_system_iterator(definition: JsValue) -> LSystemIterator;
-
-    #[wasm_bindgen(method, js_name = "current")]
-    fn current(this: &LSystemIterator, as_string: bool) -> JsValue;
-
-    #[wasm_bindgen(method, js_name = "next")]
-    fn next(this: &LSystemIterator, as_string: bool) -> JsValue;
-}
+pub mod encoding;
+pub mod expression;
+pub mod module;
+pub mod module_string;
+pub mod parser;
+pub mod production;
+pub mod query;
+pub mod random;
+pub mod timed;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -25,53 +29,631 @@ pub enum LSystemParameterValue {
     Float(f32),
 }
 
+impl LSystemParameterValue {
+    // parameters are coerced to numbers just like JavaScript's `Number` would do
+    pub fn as_f32(&self) -> f32 {
+        match self {
+            LSystemParameterValue::String(value) => value.trim().parse().unwrap_or(f32::NAN),
+            LSystemParameterValue::Float(value) => *value,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LSystemDefinition {
     alphabet: Vec<String>,
     parameters: HashMap<String, LSystemParameterValue>,
+    #[serde(default)]
     productions: Vec<String>,
     axiom: String,
+    #[serde(default)]
+    ignore: Vec<String>,
+    #[serde(default)]
+    tables: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    schedule: Vec<String>,
+    #[serde(default)]
+    homomorphism: Vec<String>,
+    #[serde(default, rename = "terminalAges")]
+    terminal_ages: HashMap<String, f32>,
+}
+
+impl LSystemDefinition {
+    pub fn alphabet(&self) -> &Vec<String> {
+        &self.alphabet
+    }
+    pub fn parameters(&self) -> &HashMap<String, LSystemParameterValue> {
+        &self.parameters
+    }
+    pub fn productions(&self) -> &Vec<String> {
+        &self.productions
+    }
+    pub fn axiom(&self) -> &str {
+        &self.axiom
+    }
+    pub fn ignore(&self) -> &Vec<String> {
+        &self.ignore
+    }
+    pub fn tables(&self) -> &HashMap<String, Vec<String>> {
+        &self.tables
+    }
+    pub fn schedule(&self) -> &Vec<String> {
+        &self.schedule
+    }
+    pub fn homomorphism(&self) -> &Vec<String> {
+        &self.homomorphism
+    }
+    pub fn terminal_ages(&self) -> &HashMap<String, f32> {
+        &self.terminal_ages
+    }
+
+    /// Returns a copy of this definition where the given parameters override its own.
+    pub fn with_parameters(&self, parameters: &HashMap<String, LSystemParameterValue>) -> Self {
+        let mut definition = self.clone();
+        definition
+            .parameters
+            .extend(parameters.iter().map(|(k, v)| (k.clone(), v.clone())));
+        definition
+    }
+}
+
+/// The maximum number of times the homomorphism is applied recursively to a module.
+const MAX_HOMOMORPHISM_DEPTH: usize = 8;
+
+/// Distinguishes the random numbers of derivation steps from those of the homomorphism.
+const DERIVATION_SALT: u64 = 0x5DEE_CE66_D1CE_5A17;
+
+/// Module strings with fewer modules than this are derived on a single thread.
+#[cfg(not(target_arch = "wasm32"))]
+const MIN_MODULES_PER_THREAD: usize = 4096;
+
+/// Removes the remainder of the branch it is part of from a derived module string.
+pub const CUT_SYMBOL: &str = "%";
+
+// removes each cut symbol and the remainder of its branch up to the branch's closing bracket (or
+// the end of the module string if it is not part of a branch) from a module string
+fn cut_branches(modules: &mut ModuleString, history: &mut Vec<ModuleHistory>) {
+    if !modules.iter().any(|m| m.name() == CUT_SYMBOL) {
+        return;
+    }
+    let mut depth = 0;
+    let keep: Vec<bool> = modules
+        .iter()
+        .map(|m| {
+            if depth == 0 {
+                if m.name() == CUT_SYMBOL {
+                    depth = 1;
+                    return false;
+                }
+                return true;
+            }
+            match m.name() {
+                "[" => depth += 1,
+                "]" => depth -= 1,
+                _ => {}
+            }
+            // the closing bracket of the cut branch is kept
+            depth == 0
+        })
+        .collect();
+    modules.retain_modules(&keep);
+    let mut i = 0;
+    history.retain(|_| {
+        i += 1;
+        keep[i - 1]
+    });
+}
+
+// the result of rewriting a range of a module string
+#[derive(Clone)]
+struct DerivedRange {
+    modules: ModuleString,
+    history: Vec<ModuleHistory>,
+    // the indices of modules starting a new lineage, whose ids are relative to the range
+    new_lineages: Vec<usize>,
+}
+
+// a derivation of the next iteration that has only been carried out up to some module
+#[derive(Clone, Default)]
+struct PartialDerivation {
+    ranges: Vec<DerivedRange>,
+    // the number of modules of the current module string that have been rewritten
+    derived: usize,
+}
+
+/// The homomorphic image of a module string while the homomorphism is applied to it in steps (see
+/// `LSystem::continue_homomorphic_image`).
+pub struct PartialImage {
+    random: Random,
+    image: ModuleString,
+    origins: Vec<usize>,
+    // one buffer per recursion depth for the successors of homomorphism productions
+    successors: Vec<ModuleString>,
+    bound_parameters: Vec<f32>,
+    // the number of modules of the original module string the homomorphism has been applied to
+    applied: usize,
+}
+
+impl PartialImage {
+    pub fn finish(self) -> HomomorphicImage<'static> {
+        HomomorphicImage {
+            modules: Cow::Owned(self.image),
+            origins: Some(self.origins),
+        }
+    }
 }
 
+/// The result of applying an L-system's homomorphism to a module string.
+/// If the L-system has no homomorphism, the image borrows the module string instead of copying it.
+pub struct HomomorphicImage<'a> {
+    modules: Cow<'a, ModuleString>,
+    // the index of the module in the original module string each module in the image was
+    // produced from, or `None` if the image is the module string itself
+    origins: Option<Vec<usize>>,
+}
+
+impl<'a> HomomorphicImage<'a> {
+    /// The image of a module string under an empty homomorphism, i.e., the module string itself.
+    pub fn identity(modules: &'a ModuleString) -> Self {
+        Self {
+            modules: Cow::Borrowed(modules),
+            origins: None,
+        }
+    }
+
+    pub fn modules(&self) -> &ModuleString {
+        &self.modules
+    }
+
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    /// The index of the module in the original module string the module at the given index in
+    /// the image was produced from.
+    pub fn origin(&self, index: usize) -> usize {
+        self.origins
+            .as_ref()
+            .map_or(index, |origins| origins[index])
+    }
+
+    /// Iterates over the modules in the image together with their origins (see `origin`).
+    pub fn iter(&self) -> impl Iterator<Item = (ModuleRef<'_>, usize)> {
+        self.modules
+            .iter()
+            .enumerate()
+            .map(|(i, module)| (module, self.origin(i)))
+    }
+}
+
+#[derive(Clone)]
 pub struct LSystem {
-    #[cfg(target_arch = "wasm32")]
-    l_system_iterator: LSystemIterator,
+    definition: LSystemDefinition,
+    productions: ProductionTable,
+    tables: HashMap<String, ProductionTable>,
+    homomorphism: ProductionTable,
+    ignored: HashSet<String>,
+    state: ModuleString,
+    history: Vec<ModuleHistory>,
+    next_id: u64,
+    iteration: usize,
+    seed: u64,
+    num_threads: Option<usize>,
+    derivation: Option<PartialDerivation>,
 }
 
 impl LSystem {
-    #[cfg(target_arch = "wasm32")]
-    pub fn new(definition: &LSystemDefinition) -> Self {
-        Self {
-            l_system_iterator: LSystemIterator::create_l_system_iterator(
-                serde_wasm_bindgen::to_value(definition).ok().unwrap(),
-            ),
+    pub fn new(definition: &LSystemDefinition) -> Result<Self, ParseError> {
+        let (axiom, productions, tables, homomorphism) = parse_l_system(definition)?.into_parts();
+        let seed = Random::from_time().next_u64();
+        Ok(Self {
+            definition: definition.clone(),
+            productions,
+            tables,
+            homomorphism,
+            ignored: definition.ignore().iter().cloned().collect(),
+            history: (0..axiom.len() as u64).map(ModuleHistory::axiom).collect(),
+            next_id: axiom.len() as u64,
+            state: axiom,
+            iteration: 0,
+            seed,
+            num_threads: None,
+            derivation: None,
+        })
+    }
+
+    pub fn definition(&self) -> &LSystemDefinition {
+        &self.definition
+    }
+
+    /// Reseeds the random number generator used for stochastic productions and `Math.random()`.
+    /// Two L-systems with the same definition and seed produce the same sequence of iterations.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Sets the number of threads module strings are derived on (native targets only).
+    /// Defaults to the available parallelism. The derived module strings don't depend on it.
+    pub fn set_num_threads(&mut self, num_threads: usize) {
+        self.num_threads = Some(num_threads.max(1));
+    }
+
+    // finds the production to apply to the module at the given index and sets `bound_parameters`
+    // to the parameters it binds
+    fn find_production<'a>(
+        productions: &'a ProductionTable,
+        modules: &ModuleString,
+        index: usize,
+        ignored: &HashSet<String>,
+        random: &mut Random,
+        bound_parameters: &mut Vec<f32>,
+    ) -> Result<Option<&'a Production>, EvaluationError> {
+        if let Some(candidates) = productions.get(modules.name(index)) {
+            for production in candidates.iter() {
+                if production.try_match(modules, index, ignored, random, bound_parameters)? {
+                    return Ok(Some(production));
+                }
+            }
         }
+        Ok(None)
     }
 
-    #[cfg(target_arch = "wasm32")]
-    pub fn next_raw(&self) -> JsValue {
-        self.l_system_iterator.next(false)
+    /// Returns the name of the table that produced the given iteration or `None` if it was produced
+    /// by the L-system's `productions` or is the axiom.
+    pub fn table(&self, iteration: usize) -> Option<&str> {
+        iteration
+            .checked_sub(1)
+            .and_then(|i| self.definition.schedule().get(i))
+            .map(|name| name.as_str())
     }
 
-    #[cfg(target_arch = "wasm32")]
-    pub fn next(&self) -> String {
-        self.l_system_iterator
-            .next(true)
-            .as_string()
-            .expect("L-System state was no String")
+    /// Returns the definition of the given applied production, i.e., the production in the
+    /// `productions` or table that produced its iteration.
+    pub fn production_definition(&self, production: AppliedProduction) -> Option<&str> {
+        let productions = match self.table(production.iteration()) {
+            Some(name) => self.definition.tables().get(name)?,
+            None => self.definition.productions(),
+        };
+        productions.get(production.index()).map(|p| p.as_str())
     }
 
-    #[cfg(target_arch = "wasm32")]
-    pub fn current_raw(&self) -> JsValue {
-        self.l_system_iterator.current(false)
+    // rewrites the modules in the given range of the current module string
+    // new lineages get ids relative to the range, s.t. ranges can be derived independently
+    fn derive_range(
+        &self,
+        productions: &ProductionTable,
+        range: Range<usize>,
+        birth: usize,
+        iteration_seed: u64,
+    ) -> Result<DerivedRange, EvaluationError> {
+        let mut modules =
+            ModuleString::with_capacity(self.state.symbols().clone(), range.len(), range.len());
+        let mut history = Vec::with_capacity(range.len());
+        let mut new_lineages = Vec::new();
+        let mut bound_parameters = Vec::new();
+        for i in range {
+            // each module uses its own random numbers, s.t. the result doesn't depend on how the
+            // module string is split
+            let mut random = Random::new(Random::new(iteration_seed ^ i as u64).next_u64());
+            if let Some(production) = Self::find_production(
+                productions,
+                &self.state,
+                i,
+                &self.ignored,
+                &mut random,
+                &mut bound_parameters,
+            )? {
+                let first_successor = modules.len();
+                let index = production.apply_into(&bound_parameters, &mut random, &mut modules)?;
+                let applied = AppliedProduction::new(birth, index);
+                let predecessor = self.state.get(i).unwrap();
+                let mut continued = false;
+                for j in first_successor..modules.len() {
+                    let module = modules.get(j).unwrap();
+                    // the first successor of the same kind continues the predecessor
+                    if !continued
+                        && module.name() == predecessor.name()
+                        && module.num_parameters() == predecessor.num_parameters()
+                    {
+                        continued = true;
+                        history.push(self.history[i].continued(applied, i));
+                    } else {
+                        new_lineages.push(history.len());
+                        history.push(ModuleHistory::produced(
+                            new_lineages.len() as u64 - 1,
+                            birth,
+                            &self.history[i],
+                            applied,
+                        ));
+                    }
+                }
+            } else {
+                modules.push_copy(&self.state, i);
+                history.push(self.history[i].copied());
+            }
+        }
+        Ok(DerivedRange {
+            modules,
+            history,
+            new_lineages,
+        })
+    }
+
+    // splits the current module string into ranges that are derived in parallel on native
+    // targets
+    #[cfg(not(target_arch = "wasm32"))]
+    fn derive_ranges(
+        &self,
+        productions: &ProductionTable,
+        range: Range<usize>,
+        birth: usize,
+        iteration_seed: u64,
+    ) -> Result<Vec<DerivedRange>, EvaluationError> {
+        let num_threads = self
+            .num_threads
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+        let len = range.len();
+        if num_threads < 2 || len < MIN_MODULES_PER_THREAD * 2 {
+            return Ok(vec![self.derive_range(
+                productions,
+                range,
+                birth,
+                iteration_seed,
+            )?]);
+        }
+        let chunk_size = (len / num_threads).max(MIN_MODULES_PER_THREAD);
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = range
+                .clone()
+                .step_by(chunk_size)
+                .map(|start| {
+                    let range = start..(start + chunk_size).min(range.end);
+                    scope
+                        .spawn(move || self.derive_range(productions, range, birth, iteration_seed))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("Derivation thread panicked"))
+                .collect()
+        })
     }
 
     #[cfg(target_arch = "wasm32")]
+    fn derive_ranges(
+        &self,
+        productions: &ProductionTable,
+        range: Range<usize>,
+        birth: usize,
+        iteration_seed: u64,
+    ) -> Result<Vec<DerivedRange>, EvaluationError> {
+        Ok(vec![self.derive_range(
+            productions,
+            range,
+            birth,
+            iteration_seed,
+        )?])
+    }
+
+    /// Rewrites up to the given number of modules of the current module string and returns true
+    /// once the next iteration has been derived completely.
+    /// Until then, the current module string stays the same, s.t. deriving large module strings
+    /// can be paused between calls.
+    /// The current state is kept and the derivation starts over if it fails.
+    pub fn continue_derivation(&mut self, max_modules: usize) -> Result<bool, EvaluationError> {
+        // iterations not covered by the schedule use the L-system's productions
+        let productions = match self.table(self.iteration + 1) {
+            Some(name) => &self.tables[name],
+            None => &self.productions,
+        };
+        let birth = self.iteration + 1;
+        // stochastic derivations don't depend on random numbers of the homomorphism or previous
+        // derivation steps
+        let iteration_seed = Random::new(self.seed ^ DERIVATION_SALT ^ birth as u64).next_u64();
+        let mut derivation = self.derivation.take().unwrap_or_default();
+        let range = derivation.derived
+            ..derivation
+                .derived
+                .saturating_add(max_modules)
+                .min(self.state.len());
+        derivation.derived = range.end;
+        derivation
+            .ranges
+            .extend(self.derive_ranges(productions, range, birth, iteration_seed)?);
+        if derivation.derived < self.state.len() {
+            self.derivation = Some(derivation);
+            return Ok(false);
+        }
+
+        // stitch the derived ranges together and make the ids of new lineages unique
+        let mut next_state = self.state.new_empty();
+        let mut next_history = Vec::with_capacity(self.history.len());
+        for range in derivation.ranges {
+            let first = next_history.len();
+            next_history.extend(range.history);
+            for &i in range.new_lineages.iter() {
+                next_history[first + i].offset_id(self.next_id);
+            }
+            self.next_id += range.new_lineages.len() as u64;
+            next_state.append(&range.modules);
+        }
+        cut_branches(&mut next_state, &mut next_history);
+        self.state = next_state;
+        self.history = next_history;
+        self.iteration += 1;
+        Ok(true)
+    }
+
+    /// The number of derivation steps applied to the axiom so far.
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    /// The history of each module in the current module string.
+    pub fn history(&self) -> &Vec<ModuleHistory> {
+        &self.history
+    }
+
+    /// Returns a copy of the current module string together with the history of its modules.
+    pub fn snapshot(&self) -> TimedSnapshot {
+        TimedSnapshot::new(self.iteration, self.state.clone(), self.history.clone())
+    }
+
+    /// The id the next new module lineage gets.
+    pub fn next_id(&self) -> u64 {
+        self.next_id
+    }
+
+    /// Continues the derivation from a snapshot of this L-system, e.g., one restored from a cache.
+    /// `next_id` must be the value of `next_id` when the snapshot was taken.
+    pub fn restore(&mut self, snapshot: TimedSnapshot, next_id: u64) {
+        let (iteration, state, history) = snapshot.into_parts();
+        self.iteration = iteration;
+        self.state = state;
+        self.history = history;
+        self.next_id = next_id;
+        self.derivation = None;
+    }
+
+    pub fn current_modules(&self) -> &ModuleString {
+        &self.state
+    }
+
+    pub fn next_modules(&mut self) -> Result<&ModuleString, EvaluationError> {
+        while !self.continue_derivation(usize::MAX)? {}
+        Ok(self.current_modules())
+    }
+
+    /// Applies the L-system's homomorphism to the current module string and returns the modules
+    /// to interpret together with the index of the module in the current module string each of
+    /// them was produced from.
+    /// The result is never fed back into the derivation.
+    pub fn homomorphic_image(&self) -> Result<HomomorphicImage<'_>, EvaluationError> {
+        self.homomorphic_image_of(&self.state, self.iteration)
+    }
+
+    /// Applies the L-system's homomorphism to the given module string of the given iteration.
+    pub fn homomorphic_image_of<'a>(
+        &self,
+        modules: &'a ModuleString,
+        iteration: usize,
+    ) -> Result<HomomorphicImage<'a>, EvaluationError> {
+        match self.start_homomorphic_image(modules, iteration) {
+            Some(mut image) => {
+                self.continue_homomorphic_image(&mut image, modules, usize::MAX)?;
+                Ok(image.finish())
+            }
+            None => Ok(HomomorphicImage::identity(modules)),
+        }
+    }
+
+    /// Starts applying the L-system's homomorphism to the given module string of the given
+    /// iteration in steps, or returns `None` if the L-system has no homomorphism.
+    pub fn start_homomorphic_image(
+        &self,
+        modules: &ModuleString,
+        iteration: usize,
+    ) -> Option<PartialImage> {
+        if self.homomorphism.is_empty() {
+            return None;
+        }
+        Some(PartialImage {
+            // stochastic homomorphisms don't consume random numbers of the derivation
+            random: Random::new(Random::new(self.seed ^ iteration as u64).next_u64()),
+            image: modules.new_empty(),
+            origins: Vec::with_capacity(modules.len()),
+            successors: (0..MAX_HOMOMORPHISM_DEPTH)
+                .map(|_| ModuleString::new(modules.symbols().clone()))
+                .collect(),
+            bound_parameters: Vec::new(),
+            applied: 0,
+        })
+    }
+
+    /// Applies the L-system's homomorphism to up to the given number of modules of the module
+    /// string a partial image was started from, and returns true once the image is complete.
+    pub fn continue_homomorphic_image(
+        &self,
+        image: &mut PartialImage,
+        modules: &ModuleString,
+        max_modules: usize,
+    ) -> Result<bool, EvaluationError> {
+        let end = image.applied.saturating_add(max_modules).min(modules.len());
+        for i in image.applied..end {
+            let length = image.image.len();
+            self.apply_homomorphism(
+                modules,
+                i,
+                &mut image.successors,
+                &mut image.random,
+                &mut image.bound_parameters,
+                &mut image.image,
+            )?;
+            image
+                .origins
+                .resize(image.origins.len() + image.image.len() - length, i);
+        }
+        image.applied = end;
+        Ok(end == modules.len())
+    }
+
+    fn apply_homomorphism(
+        &self,
+        modules: &ModuleString,
+        index: usize,
+        successors: &mut [ModuleString],
+        random: &mut Random,
+        bound_parameters: &mut Vec<f32>,
+        image: &mut ModuleString,
+    ) -> Result<(), EvaluationError> {
+        if let Some((successor, successors)) = successors.split_first_mut() {
+            if let Some(production) = Self::find_production(
+                &self.homomorphism,
+                modules,
+                index,
+                &self.ignored,
+                random,
+                bound_parameters,
+            )? {
+                *successor = successor.new_empty();
+                production.apply_into(bound_parameters, random, successor)?;
+                for i in 0..successor.len() {
+                    self.apply_homomorphism(
+                        successor,
+                        i,
+                        successors,
+                        random,
+                        bound_parameters,
+                        image,
+                    )?;
+                }
+                return Ok(());
+            }
+        }
+        image.push_copy(modules, index);
+        Ok(())
+    }
+
+    /// Sets the parameters of the query module at the given index in the current module string.
+    /// Does nothing if there is no query module at that index or if the number of values doesn't
+    /// match the module's number of parameters.
+    pub fn set_query_parameters(&mut self, index: usize, values: &[f32]) {
+        if let Some(module) = self.state.get(index) {
+            if is_query_module(module.name()) && module.num_parameters() == values.len() {
+                self.state.parameters_mut(index).copy_from_slice(values);
+            }
+        }
+    }
+
     pub fn current(&self) -> String {
-        self.l_system_iterator
-            .current(true)
-            .as_string()
-            .expect("L-System state was no String")
+        self.state.to_string()
     }
 
     #[cfg(target_arch = "wasm32")]
@@ -84,7 +666,7 @@ impl LSystem {
         LSystem::from_l_system_definitions(&l_system_definitions)
     }
 
-    #[cfg(target_arch = "wasm32")]
+    // ignores faulty L-system definitions
     pub fn from_l_system_definitions(
         definitions: &HashMap<String, HashMap<String, LSystemDefinition>>,
     ) -> HashMap<String, HashMap<String, LSystem>> {
@@ -92,7 +674,19 @@ impl LSystem {
         for (system_name, system) in definitions {
             let mut instances = HashMap::new();
             for (instance_name, instance) in system {
-                instances.insert(instance_name.clone(), LSystem::new(instance));
+                match LSystem::new(instance) {
+                    Ok(l_system) => {
+                        instances.insert(instance_name.clone(), l_system);
+                    }
+                    Err(error) => {
+                        log::error!(
+                            "Could not parse instance {} of L-system {}: {}",
+                            instance_name,
+                            system_name,
+                            error
+                        );
+                    }
+                }
             }
             l_systems.insert(system_name.clone(), instances);
         }
@@ -100,11 +694,263 @@ impl LSystem {
     }
 }
 
-#[cfg(target_arch = "wasm32")]
-impl From<JsValue> for LSystem {
-    fn from(definition: JsValue) -> Self {
-        Self {
-            l_system_iterator: LSystemIterator::create_l_system_iterator(definition),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn l_system(definition: serde_json::Value) -> LSystem {
+        let definition: LSystemDefinition = serde_json::from_value(definition).unwrap();
+        LSystem::new(&definition).unwrap()
+    }
+
+    fn derive(l_system: &mut LSystem, iterations: usize) -> String {
+        for _ in 0..iterations {
+            l_system.next_modules().unwrap();
+        }
+        l_system.current()
+    }
+
+    #[test]
+    fn derives_algae() {
+        let mut algae = l_system(json!({
+            "alphabet": ["A", "B"],
+            "parameters": {},
+            "productions": ["A -> AB", "B -> A"],
+            "axiom": "A"
+        }));
+        assert_eq!(algae.current(), "A");
+        assert_eq!(derive(&mut algae, 5), "ABAABABAABAAB");
+        assert_eq!(algae.iteration(), 5);
+        assert_eq!(algae.history().len(), algae.current_modules().len());
+    }
+
+    #[test]
+    fn derives_parametric_productions() {
+        let mut l_system = l_system(json!({
+            "alphabet": ["A(x)", "B(x,y)"],
+            "parameters": {"s": 2},
+            "productions": [
+                "A(x): x < 4 -> A(x * s)B(x, x + 1)",
+                "A(x): x >= 4 -> B(x, 0)"
+            ],
+            "axiom": "A(1)"
+        }));
+        assert_eq!(derive(&mut l_system, 1), "A(2)B(1,2)");
+        assert_eq!(derive(&mut l_system, 1), "A(4)B(2,3)B(1,2)");
+        assert_eq!(derive(&mut l_system, 1), "B(4,0)B(2,3)B(1,2)");
+    }
+
+    #[test]
+    fn keeps_modules_without_production() {
+        let mut l_system = l_system(json!({
+            "alphabet": ["F", "+", "[", "]"],
+            "parameters": {},
+            "productions": ["F -> F[+F]F"],
+            "axiom": "+F"
+        }));
+        assert_eq!(derive(&mut l_system, 2), "+F[+F]F[+F[+F]F]F[+F]F");
+    }
+
+    #[test]
+    fn cuts_branches() {
+        let mut l_system = l_system(json!({
+            "alphabet": ["A", "B", "C", "[", "]", "%"],
+            "parameters": {},
+            "productions": ["B -> %"],
+            "axiom": "A[BC[A]C]AB[C]"
+        }));
+        assert_eq!(derive(&mut l_system, 1), "A[]A");
+        assert_eq!(l_system.history().len(), l_system.current_modules().len());
+    }
+
+    #[test]
+    fn tracks_lineage() {
+        let mut l_system = l_system(json!({
+            "alphabet": ["A", "B"],
+            "parameters": {},
+            "productions": ["A -> AB"],
+            "axiom": "A"
+        }));
+        derive(&mut l_system, 2);
+        let history = l_system.history();
+        // the first successor continues its predecessor's lineage
+        assert_eq!(history[0].id(), 0);
+        assert_eq!(history[1].parent(), Some(0));
+        assert_eq!(history[1].birth(), 2);
+        assert_eq!(history[2].birth(), 1);
+        assert_ne!(history[1].id(), history[2].id());
+    }
+
+    fn stochastic_l_system(seed: u64) -> LSystem {
+        let mut l_system = l_system(json!({
+            "alphabet": ["A", "B(x)"],
+            "parameters": {},
+            "productions": [
+                "0.5; A -> AB(Math.random())",
+                "0.3; A -> B(1)A",
+                "0.2; A -> AA"
+            ],
+            "axiom": "A"
+        }));
+        l_system.set_seed(seed);
+        l_system
+    }
+
+    #[test]
+    fn derives_stochastic_productions_deterministically_per_seed() {
+        let first = derive(&mut stochastic_l_system(42), 8);
+        assert_eq!(first, derive(&mut stochastic_l_system(42), 8));
+        assert_ne!(first, derive(&mut stochastic_l_system(43), 8));
+    }
+
+    #[test]
+    fn derives_iterations_independently_of_each_other() {
+        // restoring a snapshot continues the derivation just like the original L-system
+        let mut original = stochastic_l_system(7);
+        derive(&mut original, 3);
+        let snapshot = original.snapshot();
+        let next_id = original.next_id();
+        let expected = derive(&mut original, 3);
+
+        let mut restored = stochastic_l_system(7);
+        restored.restore(snapshot, next_id);
+        assert_eq!(derive(&mut restored, 3), expected);
+    }
+
+    #[test]
+    fn chooses_successors_by_probability() {
+        let mut l_system = l_system(json!({
+            "alphabet": ["A", "B", "C"],
+            "parameters": {},
+            "productions": ["0.75; A -> B", "0.25; A -> C"],
+            "axiom": "A".repeat(4000)
+        }));
+        l_system.set_seed(1);
+        let modules = derive(&mut l_system, 1);
+        let b = modules.chars().filter(|&c| c == 'B').count();
+        assert_eq!(b + modules.chars().filter(|&c| c == 'C').count(), 4000);
+        assert!((2800..3200).contains(&b));
+    }
+
+    #[test]
+    fn derives_iterations_in_steps() {
+        let mut l_system = l_system(json!({
+            "alphabet": ["A", "B", "F", "[", "]"],
+            "parameters": {},
+            "productions": ["A < B -> AB", "A -> B[A]"],
+            "homomorphism": ["B -> FF"],
+            "axiom": "ABA"
+        }));
+        l_system.set_seed(5);
+        let mut stepwise = l_system.clone();
+        let expected = derive(&mut l_system, 4);
+        for iteration in 0..4 {
+            // the current module string stays the same until the derivation is complete
+            while !stepwise.continue_derivation(3).unwrap() {
+                assert_eq!(stepwise.iteration(), iteration);
+            }
+        }
+        assert_eq!(stepwise.current(), expected);
+
+        let modules = stepwise.current_modules();
+        let mut image = stepwise.start_homomorphic_image(modules, 4).unwrap();
+        while !stepwise
+            .continue_homomorphic_image(&mut image, modules, 2)
+            .unwrap()
+        {}
+        let image = image.finish();
+        let expected = l_system.homomorphic_image().unwrap();
+        assert_eq!(image.modules().to_string(), expected.modules().to_string());
+        assert!((0..image.len()).all(|i| image.origin(i) == expected.origin(i)));
+    }
+
+    #[test]
+    fn decodes_snapshots_of_the_previous_module_string() {
+        let mut l_system = l_system(json!({
+            "alphabet": ["A", "B"],
+            "parameters": {},
+            "productions": ["A -> AB", "B -> B"],
+            "axiom": "A"
+        }));
+        derive(&mut l_system, 3);
+        let previous_len = l_system.current_modules().len();
+        derive(&mut l_system, 1);
+        let mut encoder = encoding::Encoder::new();
+        l_system.snapshot().encode(&mut encoder);
+        let bytes = encoder.into_bytes();
+        let symbols = l_system.current_modules().symbols();
+
+        let decode = |previous_len| {
+            TimedSnapshot::decode(&mut encoding::Decoder::new(&bytes), symbols, previous_len)
+        };
+        let snapshot = decode(previous_len).unwrap();
+        assert_eq!(snapshot.modules().to_string(), l_system.current());
+        assert!(snapshot
+            .history()
+            .iter()
+            .zip(l_system.history())
+            .all(|(a, b)| a.id() == b.id() && a.predecessor() == b.predecessor()));
+        // the last module continues the last module of the previous module string
+        assert!(decode(previous_len - 1).is_none());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    // derives the given L-system on the given number of threads and encodes the result
+    fn derive_encoded(l_system: &LSystem, num_threads: usize, iterations: usize) -> Vec<u8> {
+        let mut l_system = l_system.clone();
+        l_system.set_num_threads(num_threads);
+        derive(&mut l_system, iterations);
+        let mut encoder = encoding::Encoder::new();
+        l_system.snapshot().encode(&mut encoder);
+        encoder.u64(l_system.next_id());
+        encoder.into_bytes()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn derives_stochastic_productions_independently_of_threads() {
+        let mut l_system = l_system(json!({
+            "alphabet": ["A", "B(x)"],
+            "parameters": {},
+            "productions": [
+                "0.5; A -> AB(Math.random())",
+                "0.3; A -> B(1)A",
+                "0.2; A -> AA"
+            ],
+            "axiom": "A".repeat(MIN_MODULES_PER_THREAD * 3)
+        }));
+        l_system.set_seed(3);
+        let expected = derive_encoded(&l_system, 1, 2);
+        for num_threads in [2, 3, 8] {
+            assert!(derive_encoded(&l_system, num_threads, 2) == expected);
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn derives_context_across_threads() {
+        // with two threads, the first range ends with the `B`, s.t. the contexts of its
+        // neighbours are in the other range
+        let axiom = format!(
+            "{}B{}",
+            "A".repeat(MIN_MODULES_PER_THREAD + 999),
+            "A".repeat(MIN_MODULES_PER_THREAD + 1001)
+        );
+        let l_system = l_system(json!({
+            "alphabet": ["A", "B"],
+            "parameters": {},
+            "productions": ["B < A -> B", "A > B -> B"],
+            "axiom": axiom
+        }));
+        let expected = derive_encoded(&l_system, 1, 3);
+        for num_threads in [2, 3, 8] {
+            assert!(derive_encoded(&l_system, num_threads, 3) == expected);
         }
+        let mut threaded = l_system.clone();
+        threaded.set_num_threads(2);
+        let modules = derive(&mut threaded, 3);
+        assert_eq!(modules.find('B'), Some(MIN_MODULES_PER_THREAD + 999 - 3));
+        assert_eq!(modules.matches('B').count(), 7);
     }
 }
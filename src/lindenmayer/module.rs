@@ -0,0 +1,113 @@
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ModuleParameter {
+    Number(f32),
+    String(String),
+}
+
+impl ModuleParameter {
+    pub fn as_number(&self) -> Option<f32> {
+        match self {
+            ModuleParameter::Number(number) => Some(*number),
+            ModuleParameter::String(_) => None,
+        }
+    }
+
+    // strings are coerced to NaN just like JavaScript's `Number` does for non-numeric strings
+    pub fn as_number_or_nan(&self) -> f32 {
+        self.as_number().unwrap_or(f32::NAN)
+    }
+}
+
+impl Display for ModuleParameter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModuleParameter::Number(number) => write!(f, "{}", number),
+            ModuleParameter::String(string) => write!(f, "\"{}\"", string),
+        }
+    }
+}
+
+/// A symbol of an L-system's alphabet: a name and an implicit number of parameters.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Symbol {
+    name: String,
+    num_parameters: usize,
+}
+
+impl Symbol {
+    pub fn new(name: String, num_parameters: usize) -> Self {
+        Self {
+            name,
+            num_parameters,
+        }
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn num_parameters(&self) -> usize {
+        self.num_parameters
+    }
+}
+
+impl Display for Symbol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.num_parameters == 0 {
+            write!(f, "{}", self.name)
+        } else {
+            write!(
+                f,
+                "{}({})",
+                self.name,
+                vec!["_"; self.num_parameters].join(",")
+            )
+        }
+    }
+}
+
+/// An instance of a `Symbol` with actual parameter values.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Module {
+    name: String,
+    parameters: Vec<ModuleParameter>,
+}
+
+impl Module {
+    pub fn new(name: String, parameters: Vec<ModuleParameter>) -> Self {
+        Self { name, parameters }
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn parameters(&self) -> &Vec<ModuleParameter> {
+        &self.parameters
+    }
+    pub fn num_parameters(&self) -> usize {
+        self.parameters.len()
+    }
+    pub fn matches(&self, symbol: &Symbol) -> bool {
+        self.name == symbol.name && self.parameters.len() == symbol.num_parameters
+    }
+}
+
+impl Display for Module {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.parameters.is_empty() {
+            write!(f, "{}", self.name)
+        } else {
+            write!(
+                f,
+                "{}({})",
+                self.name,
+                self.parameters
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            )
+        }
+    }
+}
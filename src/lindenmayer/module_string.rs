@@ -0,0 +1,428 @@
+use crate::lindenmayer::encoding::{Decoder, Encoder};
+use crate::lindenmayer::module::{Module, ModuleParameter, Symbol};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::mem::size_of;
+use std::sync::Arc;
+
+/// Interns the names of an L-system's modules and the string literals used as module parameters.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolTable {
+    names: Vec<String>,
+    ids: HashMap<String, u32>,
+}
+
+impl SymbolTable {
+    /// Returns the id of the given name, adding it to the table if necessary.
+    pub fn intern(&mut self, name: &str) -> u32 {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = self.names.len() as u32;
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        id
+    }
+    pub fn id(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+    pub fn name(&self, id: u32) -> &str {
+        &self.names[id as usize]
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct CompactModule {
+    symbol: u32,
+    start: u32,
+    len: u32,
+}
+
+/// A module string stored as interned symbol ids and a flat parameter arena, s.t. deriving and
+/// interpreting it doesn't allocate per module.
+///
+/// String parameters are stored as `NaN` in the arena, i.e., they are coerced to numbers just
+/// like in expressions, and their interned values are kept separately.
+#[derive(Clone, Debug)]
+pub struct ModuleString {
+    symbols: Arc<SymbolTable>,
+    modules: Vec<CompactModule>,
+    parameters: Vec<f32>,
+    // pairs of an index into `parameters` and an interned string, sorted by index
+    strings: Vec<(u32, u32)>,
+}
+
+impl ModuleString {
+    pub fn new(symbols: Arc<SymbolTable>) -> Self {
+        Self::with_capacity(symbols, 0, 0)
+    }
+
+    pub fn with_capacity(symbols: Arc<SymbolTable>, modules: usize, parameters: usize) -> Self {
+        Self {
+            symbols,
+            modules: Vec::with_capacity(modules),
+            parameters: Vec::with_capacity(parameters),
+            strings: Vec::new(),
+        }
+    }
+
+    /// Creates a module string from the given modules, whose names and string parameters must
+    /// have been interned in the given symbol table.
+    pub fn from_modules(symbols: Arc<SymbolTable>, modules: &[Module]) -> Self {
+        let mut string = Self::with_capacity(symbols, modules.len(), 0);
+        for module in modules {
+            string.push_module(string.symbol_id(module.name()));
+            for parameter in module.parameters() {
+                match parameter {
+                    ModuleParameter::Number(number) => string.push_parameter(*number),
+                    ModuleParameter::String(s) => string.push_string_parameter(string.symbol_id(s)),
+                }
+            }
+        }
+        string
+    }
+
+    fn symbol_id(&self, name: &str) -> u32 {
+        self.symbols
+            .id(name)
+            .unwrap_or_else(|| panic!("Symbol {} has not been interned", name))
+    }
+
+    /// Returns an empty module string sharing this string's symbol table.
+    pub fn new_empty(&self) -> Self {
+        Self::with_capacity(
+            self.symbols.clone(),
+            self.modules.len(),
+            self.parameters.len(),
+        )
+    }
+
+    pub fn symbols(&self) -> &Arc<SymbolTable> {
+        &self.symbols
+    }
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    /// The number of bytes occupied by the modules and their parameters.
+    pub fn memory_usage(&self) -> usize {
+        self.modules.len() * size_of::<CompactModule>()
+            + self.parameters.len() * size_of::<f32>()
+            + self.strings.len() * size_of::<(u32, u32)>()
+    }
+
+    pub fn get(&self, index: usize) -> Option<ModuleRef<'_>> {
+        (index < self.modules.len()).then_some(ModuleRef {
+            string: self,
+            index,
+        })
+    }
+    pub fn iter(&self) -> impl Iterator<Item = ModuleRef<'_>> {
+        (0..self.modules.len()).map(move |index| ModuleRef {
+            string: self,
+            index,
+        })
+    }
+
+    pub fn name(&self, index: usize) -> &str {
+        self.symbols.name(self.modules[index].symbol)
+    }
+    pub fn parameters(&self, index: usize) -> &[f32] {
+        let module = self.modules[index];
+        &self.parameters[module.start as usize..(module.start + module.len) as usize]
+    }
+    /// The numeric parameters of the module at the given index.
+    /// String parameters must not be overwritten.
+    pub fn parameters_mut(&mut self, index: usize) -> &mut [f32] {
+        let module = self.modules[index];
+        &mut self.parameters[module.start as usize..(module.start + module.len) as usize]
+    }
+    /// Returns the value of the given parameter of the module at the given index if it is a
+    /// string parameter.
+    pub fn string_parameter(&self, index: usize, parameter: usize) -> Option<&str> {
+        let module = self.modules[index];
+        if parameter >= module.len as usize {
+            return None;
+        }
+        let position = module.start + parameter as u32;
+        self.strings
+            .binary_search_by_key(&position, |&(p, _)| p)
+            .ok()
+            .map(|i| self.symbols.name(self.strings[i].1))
+    }
+
+    /// Appends a module without parameters, parameters are added via `push_parameter` and
+    /// `push_string_parameter`.
+    pub fn push_module(&mut self, symbol: u32) {
+        self.modules.push(CompactModule {
+            symbol,
+            start: self.parameters.len() as u32,
+            len: 0,
+        });
+    }
+    /// Appends a parameter to the last module.
+    pub fn push_parameter(&mut self, value: f32) {
+        self.parameters.push(value);
+        self.modules
+            .last_mut()
+            .expect("Pushed parameter to empty module string")
+            .len += 1;
+    }
+    /// Appends an interned string parameter to the last module.
+    pub fn push_string_parameter(&mut self, string: u32) {
+        self.strings.push((self.parameters.len() as u32, string));
+        self.push_parameter(f32::NAN);
+    }
+    /// Appends a copy of the module at the given index in another module string sharing this
+    /// string's symbol table.
+    pub fn push_copy(&mut self, other: &ModuleString, index: usize) {
+        let module = other.modules[index];
+        self.push_module(module.symbol);
+        let start = module.start as usize;
+        let end = start + module.len as usize;
+        let offset = self.parameters.len() as u32;
+        self.parameters
+            .extend_from_slice(&other.parameters[start..end]);
+        self.modules.last_mut().unwrap().len = module.len;
+        let first = other.strings.partition_point(|&(p, _)| p < module.start);
+        for &(p, s) in other.strings[first..]
+            .iter()
+            .take_while(|&&(p, _)| p < end as u32)
+        {
+            self.strings.push((p - module.start + offset, s));
+        }
+    }
+
+    /// Appends all modules of another module string sharing this string's symbol table.
+    pub fn append(&mut self, other: &ModuleString) {
+        let offset = self.parameters.len() as u32;
+        self.modules
+            .extend(other.modules.iter().map(|m| CompactModule {
+                start: m.start + offset,
+                ..*m
+            }));
+        self.parameters.extend_from_slice(&other.parameters);
+        self.strings
+            .extend(other.strings.iter().map(|&(p, s)| (p + offset, s)));
+    }
+
+    /// Keeps only the modules for which `keep` is true.
+    pub fn retain_modules(&mut self, keep: &[bool]) {
+        let mut retained = self.new_empty();
+        for (index, _) in keep.iter().enumerate().filter(|(_, &k)| k) {
+            retained.push_copy(self, index);
+        }
+        *self = retained;
+    }
+}
+
+impl ModuleString {
+    /// Writes the module string into a binary blob.
+    /// Symbols are written by name, s.t. the blob can be read using another symbol table.
+    pub fn encode(&self, encoder: &mut Encoder) {
+        encoder.u64(self.symbols.names.len() as u64);
+        for name in self.symbols.names.iter() {
+            encoder.str(name);
+        }
+        let modules: Vec<[u32; 3]> = self
+            .modules
+            .iter()
+            .map(|m| [m.symbol, m.start, m.len])
+            .collect();
+        encoder.slice(&modules);
+        encoder.slice(&self.parameters);
+        let strings: Vec<[u32; 2]> = self.strings.iter().map(|&(p, s)| [p, s]).collect();
+        encoder.slice(&strings);
+    }
+
+    /// Reads a module string written by `encode`.
+    /// Returns `None` if the blob is invalid or refers to symbols not interned in the given table.
+    pub fn decode(decoder: &mut Decoder, symbols: &Arc<SymbolTable>) -> Option<Self> {
+        let num_names = decoder.usize()?;
+        let mut ids = Vec::new();
+        for _ in 0..num_names {
+            ids.push(symbols.id(decoder.str()?));
+        }
+        let id = |symbol: u32| ids.get(symbol as usize).copied().flatten();
+
+        let modules: Vec<[u32; 3]> = decoder.slice()?;
+        let parameters: Vec<f32> = decoder.slice()?;
+        let modules = modules
+            .into_iter()
+            .map(|[symbol, start, len]| {
+                (start as u64 + len as u64 <= parameters.len() as u64).then_some(())?;
+                Some(CompactModule {
+                    symbol: id(symbol)?,
+                    start,
+                    len,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let strings = decoder
+            .slice::<[u32; 2]>()?
+            .into_iter()
+            .map(|[p, s]| Some((p, id(s)?)))
+            .collect::<Option<Vec<_>>>()?;
+        // string parameters are looked up by a binary search over their indices
+        if !strings.windows(2).all(|w| w[0].0 < w[1].0)
+            || strings
+                .last()
+                .is_some_and(|&(p, _)| p as usize >= parameters.len())
+        {
+            return None;
+        }
+        Some(Self {
+            symbols: symbols.clone(),
+            modules,
+            parameters,
+            strings,
+        })
+    }
+}
+
+impl Display for ModuleString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for module in self.iter() {
+            write!(f, "{}", module)?;
+        }
+        Ok(())
+    }
+}
+
+/// A module in a `ModuleString`.
+#[derive(Copy, Clone)]
+pub struct ModuleRef<'a> {
+    string: &'a ModuleString,
+    index: usize,
+}
+
+impl<'a> ModuleRef<'a> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+    pub fn name(&self) -> &'a str {
+        self.string.name(self.index)
+    }
+    /// The module's parameters, where string parameters are `NaN`.
+    pub fn parameters(&self) -> &'a [f32] {
+        self.string.parameters(self.index)
+    }
+    pub fn num_parameters(&self) -> usize {
+        self.string.modules[self.index].len as usize
+    }
+    pub fn string_parameter(&self, parameter: usize) -> Option<&'a str> {
+        self.string.string_parameter(self.index, parameter)
+    }
+    pub fn matches(&self, symbol: &Symbol) -> bool {
+        self.name() == symbol.name() && self.num_parameters() == symbol.num_parameters()
+    }
+    pub fn to_module(&self) -> Module {
+        Module::new(
+            self.name().to_string(),
+            self.parameters()
+                .iter()
+                .enumerate()
+                .map(|(i, &p)| match self.string_parameter(i) {
+                    Some(s) => ModuleParameter::String(s.to_string()),
+                    None => ModuleParameter::Number(p),
+                })
+                .collect(),
+        )
+    }
+}
+
+impl<'a> Display for ModuleRef<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())?;
+        if self.num_parameters() > 0 {
+            write!(f, "(")?;
+            for (i, p) in self.parameters().iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                match self.string_parameter(i) {
+                    Some(s) => write!(f, "\"{}\"", s)?,
+                    None => write!(f, "{}", p)?,
+                }
+            }
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(names: &[&str]) -> Arc<SymbolTable> {
+        let mut symbols = SymbolTable::default();
+        for name in names {
+            symbols.intern(name);
+        }
+        Arc::new(symbols)
+    }
+
+    // F(1,"leaf")+F(2.5)
+    fn module_string(symbols: &Arc<SymbolTable>) -> ModuleString {
+        let mut modules = ModuleString::new(symbols.clone());
+        modules.push_module(symbols.id("F").unwrap());
+        modules.push_parameter(1.);
+        modules.push_string_parameter(symbols.id("leaf").unwrap());
+        modules.push_module(symbols.id("+").unwrap());
+        modules.push_module(symbols.id("F").unwrap());
+        modules.push_parameter(2.5);
+        modules
+    }
+
+    fn encode(modules: &ModuleString) -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        modules.encode(&mut encoder);
+        encoder.into_bytes()
+    }
+
+    #[test]
+    fn decodes_encoded_module_strings_with_another_symbol_table() {
+        let modules = module_string(&symbols(&["F", "+", "leaf"]));
+        let bytes = encode(&modules);
+        let other = symbols(&["leaf", "X", "+", "F"]);
+        let decoded = ModuleString::decode(&mut Decoder::new(&bytes), &other).unwrap();
+        assert_eq!(decoded.to_string(), "F(1,\"leaf\")+F(2.5)");
+        assert_eq!(decoded.string_parameter(0, 1), Some("leaf"));
+        assert_eq!(encode(&decoded), encode(&module_string(&other)));
+    }
+
+    #[test]
+    fn rejects_truncated_and_corrupted_module_strings() {
+        let table = symbols(&["F", "+", "leaf"]);
+        let bytes = encode(&module_string(&table));
+        for len in 0..bytes.len() {
+            assert!(ModuleString::decode(&mut Decoder::new(&bytes[..len]), &table).is_none());
+        }
+        // the blob's symbols must be interned in the table
+        let missing = symbols(&["F", "+"]);
+        assert!(ModuleString::decode(&mut Decoder::new(&bytes), &missing).is_none());
+
+        let decode = |modules: &[[u32; 3]], strings: &[[u32; 2]]| {
+            let mut encoder = Encoder::new();
+            encoder.u64(1);
+            encoder.str("F");
+            encoder.slice(modules);
+            encoder.slice(&[0f32, 1.]);
+            encoder.slice(strings);
+            ModuleString::decode(&mut Decoder::new(&encoder.into_bytes()), &table)
+        };
+        assert!(decode(&[[0, 0, 2]], &[[1, 0]]).is_some());
+        // unknown symbol
+        assert!(decode(&[[1, 0, 2]], &[]).is_none());
+        // parameters beyond the arena
+        assert!(decode(&[[0, 1, 2]], &[]).is_none());
+        assert!(decode(&[[0, u32::MAX, 2]], &[]).is_none());
+        // string parameters beyond the arena or out of order
+        assert!(decode(&[[0, 0, 2]], &[[2, 0]]).is_none());
+        assert!(decode(&[[0, 0, 2]], &[[1, 0], [0, 0]]).is_none());
+    }
+}
@@ -0,0 +1,853 @@
+use crate::lindenmayer::expression::{Expression, ExpressionError};
+use crate::lindenmayer::module::{Module, ModuleParameter, Symbol};
+use crate::lindenmayer::module_string::{ModuleString, SymbolTable};
+use crate::lindenmayer::production::{
+    Production, Successor, SuccessorModule, SuccessorParameter, BRANCH_END, BRANCH_START,
+};
+use crate::lindenmayer::query::{is_query_module, num_query_parameters};
+use crate::lindenmayer::LSystemDefinition;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+const PROBABILITY_SEPARATOR: &str = ";";
+const LEFT_CONTEXT_SEPARATOR: &str = "<";
+const RIGHT_CONTEXT_SEPARATOR: &str = ">";
+const CONDITION_SEPARATOR: &str = ":";
+const SUCCESSOR_SEPARATOR: &str = "->";
+
+/// The part of an `LSystemDefinition` a `ParseError` occurred in.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", content = "index", rename_all = "camelCase")]
+pub enum ParseErrorLocation {
+    Alphabet(usize),
+    Axiom,
+    Production(usize),
+    TableProduction(String, usize),
+    Schedule(usize),
+    Homomorphism(usize),
+}
+
+impl Display for ParseErrorLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseErrorLocation::Alphabet(index) => write!(f, "alphabet symbol {}", index),
+            ParseErrorLocation::Axiom => write!(f, "axiom"),
+            ParseErrorLocation::Production(index) => write!(f, "production {}", index),
+            ParseErrorLocation::TableProduction(table, index) => {
+                write!(f, "production {} of table {}", index, table)
+            }
+            ParseErrorLocation::Schedule(index) => write!(f, "schedule entry {}", index),
+            ParseErrorLocation::Homomorphism(index) => {
+                write!(f, "homomorphism production {}", index)
+            }
+        }
+    }
+}
+
+/// An error in an `LSystemDefinition`.
+/// The column is the 1-based index of the character in `source` at which the error was detected.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseError {
+    location: ParseErrorLocation,
+    source: String,
+    column: usize,
+    message: String,
+    snippet: String,
+}
+
+impl ParseError {
+    fn new(location: ParseErrorLocation, source: &str, error: SyntaxError) -> Self {
+        // `error.at` is always a slice of `source`
+        let offset = (error.at.as_ptr() as usize)
+            .saturating_sub(source.as_ptr() as usize)
+            .min(source.len());
+        let column = source[..offset].chars().count() + 1;
+        Self {
+            location,
+            source: source.to_string(),
+            column,
+            message: error.message,
+            snippet: format!("{}\n{}^", source, " ".repeat(column - 1)),
+        }
+    }
+
+    pub fn location(&self) -> &ParseErrorLocation {
+        &self.location
+    }
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+    pub fn column(&self) -> usize {
+        self.column
+    }
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The erroneous source followed by a line with a caret pointing at the error's column.
+    pub fn snippet(&self) -> &str {
+        &self.snippet
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid {}, column {}: {}\n{}",
+            self.location,
+            self.column,
+            self.message,
+            self.snippet()
+        )
+    }
+}
+
+/// An error in a part of a definition's source.
+/// `at` is the slice of the source starting where the error was detected.
+struct SyntaxError<'a> {
+    at: &'a str,
+    message: String,
+}
+
+impl<'a> SyntaxError<'a> {
+    fn new(at: &'a str, message: String) -> Self {
+        Self { at, message }
+    }
+
+    fn from_expression_error(source: &'a str, error: ExpressionError) -> Self {
+        let offset = source
+            .char_indices()
+            .nth(error.position())
+            .map_or(source.len(), |(i, _)| i);
+        Self::new(&source[offset..], error.kind().to_string())
+    }
+}
+
+/// A module as it occurs in a definition's source, i.e., a name and the unparsed source of its
+/// parameters.
+struct RawModule<'a> {
+    name: &'a str,
+    parameters: Vec<&'a str>,
+}
+
+/// Productions grouped by the name of the symbol they replace.
+pub type ProductionTable = HashMap<String, Vec<Production>>;
+
+/// The result of parsing an `LSystemDefinition`.
+pub struct ParsedLSystem {
+    axiom: ModuleString,
+    productions: ProductionTable,
+    tables: HashMap<String, ProductionTable>,
+    homomorphism: ProductionTable,
+}
+
+impl ParsedLSystem {
+    /// The axiom, whose symbol table holds all module names and string parameters used by the
+    /// L-system.
+    pub fn axiom(&self) -> &ModuleString {
+        &self.axiom
+    }
+    pub fn productions(&self) -> &ProductionTable {
+        &self.productions
+    }
+    pub fn tables(&self) -> &HashMap<String, ProductionTable> {
+        &self.tables
+    }
+    pub fn homomorphism(&self) -> &ProductionTable {
+        &self.homomorphism
+    }
+    pub fn into_parts(
+        self,
+    ) -> (
+        ModuleString,
+        ProductionTable,
+        HashMap<String, ProductionTable>,
+        ProductionTable,
+    ) {
+        (self.axiom, self.productions, self.tables, self.homomorphism)
+    }
+}
+
+// finds the index of the parenthesis closing the one opened at `open_index`
+fn find_closing_parenthesis(source: &str, open_index: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in source[open_index..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open_index + i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// splits a comma separated list at commas that are not enclosed in parentheses
+fn split_parameters(source: &str) -> Vec<&str> {
+    if source.trim().is_empty() {
+        return Vec::new();
+    }
+    let mut parameters = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in source.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parameters.push(source[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parameters.push(source[start..].trim());
+    parameters
+}
+
+fn parse_symbol(source: &str) -> Result<Symbol, SyntaxError<'_>> {
+    let source = source.trim();
+    let symbol = if let Some(open_index) = source.find('(') {
+        let close_index = find_closing_parenthesis(source, open_index).ok_or_else(|| {
+            SyntaxError::new(
+                &source[open_index..],
+                "Missing closing parenthesis".to_string(),
+            )
+        })?;
+        let name = source[..open_index].trim();
+        if name.is_empty() {
+            return Err(SyntaxError::new(source, "Symbol has no name".to_string()));
+        }
+        Symbol::new(
+            name.to_string(),
+            split_parameters(&source[open_index + 1..close_index]).len(),
+        )
+    } else if source.is_empty() {
+        return Err(SyntaxError::new(source, "Symbol has no name".to_string()));
+    } else {
+        Symbol::new(source.to_string(), 0)
+    };
+    if symbol.name().starts_with('?') {
+        if !is_query_module(symbol.name()) {
+            return Err(SyntaxError::new(
+                source,
+                format!("Unknown query module: {}", symbol),
+            ));
+        }
+        if let Some(num_parameters) = num_query_parameters(symbol.name()) {
+            if symbol.num_parameters() != num_parameters {
+                return Err(SyntaxError::new(
+                    source,
+                    format!(
+                        "Query module {} must have {} parameters",
+                        symbol.name(),
+                        num_parameters
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(symbol)
+}
+
+fn parse_alphabet(alphabet: &[String]) -> Result<Vec<Symbol>, ParseError> {
+    let mut symbols = alphabet
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            parse_symbol(s).map_err(|e| ParseError::new(ParseErrorLocation::Alphabet(i), s, e))
+        })
+        .collect::<Result<Vec<Symbol>, ParseError>>()?;
+    // sort from most specific to least specific, s.t. longer names are matched first
+    symbols.sort_by(|a, b| {
+        b.name()
+            .len()
+            .cmp(&a.name().len())
+            .then_with(|| a.name().cmp(b.name()))
+            .then_with(|| a.num_parameters().cmp(&b.num_parameters()))
+    });
+    Ok(symbols)
+}
+
+// splits a string of modules into its modules using the symbols of the L-system's alphabet
+fn parse_module_string<'a>(
+    source: &'a str,
+    symbols: &[Symbol],
+) -> Result<Vec<RawModule<'a>>, SyntaxError<'a>> {
+    let mut modules = Vec::new();
+    let mut rest = source.trim_start();
+    while !rest.is_empty() {
+        let mut found = None;
+        for symbol in symbols.iter() {
+            if !rest.starts_with(symbol.name()) {
+                continue;
+            }
+            let after_name = &rest[symbol.name().len()..];
+            if after_name.starts_with('(') {
+                if let Some(close_index) = find_closing_parenthesis(after_name, 0) {
+                    let parameters = split_parameters(&after_name[1..close_index]);
+                    if parameters.len() == symbol.num_parameters() {
+                        found = Some((
+                            RawModule {
+                                name: &rest[..symbol.name().len()],
+                                parameters,
+                            },
+                            symbol.name().len() + close_index + 1,
+                        ));
+                        break;
+                    }
+                }
+            } else if symbol.num_parameters() == 0 {
+                found = Some((
+                    RawModule {
+                        name: &rest[..symbol.name().len()],
+                        parameters: Vec::new(),
+                    },
+                    symbol.name().len(),
+                ));
+                break;
+            }
+        }
+        if let Some((module, length)) = found {
+            modules.push(module);
+            rest = rest[length..].trim_start();
+        } else {
+            return Err(SyntaxError::new(
+                rest,
+                format!(
+                    "Incomplete alphabet [{}] for modules {}",
+                    symbols
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                    rest
+                ),
+            ));
+        }
+    }
+    Ok(modules)
+}
+
+fn parse_string_literal(source: &str) -> Option<String> {
+    if source.len() >= 2 && source.starts_with('"') && source.ends_with('"') {
+        Some(source[1..source.len() - 1].to_string())
+    } else {
+        None
+    }
+}
+
+fn parse_axiom<'a>(
+    axiom: &'a str,
+    symbols: &[Symbol],
+    globals: &HashMap<String, f32>,
+) -> Result<Vec<Module>, SyntaxError<'a>> {
+    let mut random = crate::lindenmayer::random::Random::new(0);
+    parse_module_string(axiom, symbols)?
+        .iter()
+        .map(|m| {
+            let parameters = m
+                .parameters
+                .iter()
+                .map(|p| {
+                    if let Some(string) = parse_string_literal(p) {
+                        Ok(ModuleParameter::String(string))
+                    } else {
+                        let expression = Expression::parse(p, &[], globals)
+                            .map_err(|e| SyntaxError::from_expression_error(p, e))?;
+                        expression
+                            .evaluate(&[], &mut random)
+                            .map(ModuleParameter::Number)
+                            .map_err(|e| SyntaxError::new(p, e.to_string()))
+                    }
+                })
+                .collect::<Result<Vec<ModuleParameter>, SyntaxError>>()?;
+            Ok(Module::new(m.name.to_string(), parameters))
+        })
+        .collect()
+}
+
+// parses a module declaration, i.e., the main module or its context, and collects the declared
+// parameter names
+fn parse_module_declarations<'a>(
+    source: &'a str,
+    symbols: &[Symbol],
+    parameter_names: &mut Vec<String>,
+) -> Result<Vec<Symbol>, SyntaxError<'a>> {
+    parse_module_string(source, symbols)?
+        .iter()
+        .map(|m| {
+            for p in m.parameters.iter() {
+                if p.is_empty() {
+                    return Err(SyntaxError::new(
+                        p,
+                        format!("Unnamed parameter in module declaration {}", m.name),
+                    ));
+                }
+                if parameter_names.iter().any(|n| n == p) {
+                    return Err(SyntaxError::new(
+                        p,
+                        format!("Duplicate parameter name {}", p),
+                    ));
+                }
+                parameter_names.push(p.to_string());
+            }
+            Ok(Symbol::new(m.name.to_string(), m.parameters.len()))
+        })
+        .collect()
+}
+
+struct ProductionSpecification {
+    index: usize,
+    probability: Option<f32>,
+    symbol: Symbol,
+    left_context: Vec<Symbol>,
+    right_context: Vec<Symbol>,
+    condition: Option<Expression>,
+    modules: Vec<SuccessorModule>,
+}
+
+impl ProductionSpecification {
+    fn has_same_requirements(&self, other: &Self) -> bool {
+        self.symbol == other.symbol
+            && self.left_context == other.left_context
+            && self.right_context == other.right_context
+            && self.condition == other.condition
+    }
+}
+
+fn parse_production<'a>(
+    index: usize,
+    source: &'a str,
+    symbols: &[Symbol],
+    globals: &HashMap<String, f32>,
+    symbol_table: &mut SymbolTable,
+) -> Result<ProductionSpecification, SyntaxError<'a>> {
+    let successor_index = source.find(SUCCESSOR_SEPARATOR).ok_or_else(|| {
+        SyntaxError::new(
+            &source[source.len()..],
+            format!("Missing '{}'", SUCCESSOR_SEPARATOR),
+        )
+    })?;
+    let head = &source[..successor_index];
+    let body = &source[successor_index + SUCCESSOR_SEPARATOR.len()..];
+
+    let (probability, head) = if let Some(index) = head.find(PROBABILITY_SEPARATOR) {
+        let probability_source = head[..index].trim();
+        let probability = if probability_source.is_empty() {
+            None
+        } else {
+            Some(probability_source.parse::<f32>().map_err(|_| {
+                SyntaxError::new(
+                    probability_source,
+                    format!("Invalid probability {}", probability_source),
+                )
+            })?)
+        };
+        (probability, &head[index + PROBABILITY_SEPARATOR.len()..])
+    } else {
+        (None, head)
+    };
+
+    let (declaration, condition_source) = if let Some(index) = head.find(CONDITION_SEPARATOR) {
+        (
+            &head[..index],
+            Some(head[index + CONDITION_SEPARATOR.len()..].trim()),
+        )
+    } else {
+        (head, None)
+    };
+
+    let (left_context_source, declaration) =
+        if let Some(index) = declaration.find(LEFT_CONTEXT_SEPARATOR) {
+            (
+                &declaration[..index],
+                &declaration[index + LEFT_CONTEXT_SEPARATOR.len()..],
+            )
+        } else {
+            ("", declaration)
+        };
+    let (module_source, right_context_source) =
+        if let Some(index) = declaration.find(RIGHT_CONTEXT_SEPARATOR) {
+            (
+                &declaration[..index],
+                &declaration[index + RIGHT_CONTEXT_SEPARATOR.len()..],
+            )
+        } else {
+            (declaration, "")
+        };
+
+    // parameters are bound in the order: module, left context, right context
+    let mut module_parameters = Vec::new();
+    let mut symbol = parse_module_declarations(module_source, symbols, &mut module_parameters)?;
+    if symbol.len() != 1 {
+        return Err(SyntaxError::new(
+            module_source.trim_start(),
+            format!(
+                "Expected exactly one module in production's module declaration but got {}",
+                symbol.len()
+            ),
+        ));
+    }
+    let symbol = symbol.remove(0);
+    let mut parameters = module_parameters;
+    let left_context = parse_module_declarations(left_context_source, symbols, &mut parameters)?;
+    // the left context is searched towards the root of the branching structure, so branches in
+    // the left context can never match
+    if left_context
+        .iter()
+        .any(|s| s.name() == BRANCH_START || s.name() == BRANCH_END)
+    {
+        return Err(SyntaxError::new(
+            left_context_source.trim_start(),
+            "Branches are not allowed in the left context".to_string(),
+        ));
+    }
+    let right_context = parse_module_declarations(right_context_source, symbols, &mut parameters)?;
+
+    let condition = match condition_source {
+        Some(condition) if !condition.is_empty() => Some(
+            Expression::parse(condition, &parameters, globals)
+                .map_err(|e| SyntaxError::from_expression_error(condition, e))?,
+        ),
+        _ => None,
+    };
+
+    let modules = parse_module_string(body, symbols)?
+        .iter()
+        .map(|m| {
+            let module_parameters = m
+                .parameters
+                .iter()
+                .map(|p| {
+                    if let Some(string) = parse_string_literal(p) {
+                        Ok(SuccessorParameter::String(symbol_table.intern(&string)))
+                    } else {
+                        Expression::parse(p, &parameters, globals)
+                            .map(SuccessorParameter::Expression)
+                            .map_err(|e| SyntaxError::from_expression_error(p, e))
+                    }
+                })
+                .collect::<Result<Vec<SuccessorParameter>, SyntaxError>>()?;
+            Ok(SuccessorModule::new(
+                symbol_table.intern(m.name),
+                module_parameters,
+            ))
+        })
+        .collect::<Result<Vec<SuccessorModule>, SyntaxError>>()?;
+
+    Ok(ProductionSpecification {
+        index,
+        probability,
+        symbol,
+        left_context,
+        right_context,
+        condition,
+        modules,
+    })
+}
+
+// groups productions with the same requirements into stochastic productions
+fn group_productions(specifications: Vec<ProductionSpecification>) -> Vec<Production> {
+    let mut groups: Vec<Vec<ProductionSpecification>> = Vec::new();
+    for specification in specifications {
+        if let Some(group) = groups
+            .iter_mut()
+            .find(|g| g[0].has_same_requirements(&specification))
+        {
+            group.push(specification);
+        } else {
+            groups.push(vec![specification]);
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|group| {
+            let num_implicit = group.iter().filter(|s| s.probability.is_none()).count();
+            let sum_explicit: f32 = group.iter().filter_map(|s| s.probability).sum();
+            let implicit_probability = if num_implicit > 0 {
+                ((1.0 - sum_explicit) / num_implicit as f32).max(0.)
+            } else {
+                0.
+            };
+            let mut group = group.into_iter();
+            let first = group.next().unwrap();
+            let mut successors = vec![Successor::new(
+                first.index,
+                first.probability.unwrap_or(implicit_probability),
+                first.modules,
+            )];
+            successors.extend(group.map(|s| {
+                Successor::new(
+                    s.index,
+                    s.probability.unwrap_or(implicit_probability),
+                    s.modules,
+                )
+            }));
+            Production::new(
+                first.symbol,
+                first.left_context,
+                first.right_context,
+                first.condition,
+                successors,
+            )
+        })
+        .collect()
+}
+
+pub fn parse_l_system(definition: &LSystemDefinition) -> Result<ParsedLSystem, ParseError> {
+    let symbols = parse_alphabet(definition.alphabet())?;
+    let globals: HashMap<String, f32> = definition
+        .parameters()
+        .iter()
+        .map(|(name, value)| (name.clone(), value.as_f32()))
+        .collect();
+
+    let axiom = parse_axiom(definition.axiom(), &symbols, &globals)
+        .map_err(|e| ParseError::new(ParseErrorLocation::Axiom, definition.axiom(), e))?;
+    let mut symbol_table = SymbolTable::default();
+    for module in axiom.iter() {
+        symbol_table.intern(module.name());
+        for parameter in module.parameters() {
+            if let ModuleParameter::String(string) = parameter {
+                symbol_table.intern(string);
+            }
+        }
+    }
+
+    let productions = parse_productions(
+        definition.productions(),
+        &symbols,
+        &globals,
+        &mut symbol_table,
+        ParseErrorLocation::Production,
+    )?;
+    let mut tables = HashMap::new();
+    for (name, table) in definition.tables() {
+        tables.insert(
+            name.clone(),
+            parse_productions(table, &symbols, &globals, &mut symbol_table, |i| {
+                ParseErrorLocation::TableProduction(name.clone(), i)
+            })?,
+        );
+    }
+    for (i, name) in definition.schedule().iter().enumerate() {
+        if !tables.contains_key(name) {
+            return Err(ParseError::new(
+                ParseErrorLocation::Schedule(i),
+                name,
+                SyntaxError::new(name, format!("Unknown table: {}", name)),
+            ));
+        }
+    }
+
+    let homomorphism = parse_productions(
+        definition.homomorphism(),
+        &symbols,
+        &globals,
+        &mut symbol_table,
+        ParseErrorLocation::Homomorphism,
+    )?;
+
+    Ok(ParsedLSystem {
+        axiom: ModuleString::from_modules(Arc::new(symbol_table), &axiom),
+        productions,
+        tables,
+        homomorphism,
+    })
+}
+
+// parses a list of productions and groups them by the name of the symbol they replace
+fn parse_productions(
+    sources: &[String],
+    symbols: &[Symbol],
+    globals: &HashMap<String, f32>,
+    symbol_table: &mut SymbolTable,
+    location: impl Fn(usize) -> ParseErrorLocation,
+) -> Result<ProductionTable, ParseError> {
+    let specifications = sources
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            parse_production(i, p, symbols, globals, symbol_table)
+                .map_err(|e| ParseError::new(location(i), p, e))
+        })
+        .collect::<Result<Vec<ProductionSpecification>, ParseError>>()?;
+
+    let mut productions: ProductionTable = HashMap::new();
+    for production in group_productions(specifications) {
+        productions
+            .entry(production.symbol().name().to_string())
+            .or_default()
+            .push(production);
+    }
+    // the most specific candidates are checked first, candidates with the same rank keep their
+    // order of definition
+    for candidates in productions.values_mut() {
+        candidates.sort_by_key(|p| std::cmp::Reverse(p.rank()));
+    }
+    Ok(productions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(definition: serde_json::Value) -> Result<ParsedLSystem, ParseError> {
+        parse_l_system(&serde_json::from_value(definition).unwrap())
+    }
+
+    fn parse_error(definition: serde_json::Value) -> ParseError {
+        match parse(definition) {
+            Ok(_) => panic!("expected a parse error"),
+            Err(error) => error,
+        }
+    }
+
+    #[test]
+    fn parses_productions() {
+        let parsed = parse(json!({
+            "alphabet": ["A(x)", "B", "[", "]", "+(a)"],
+            "parameters": {"angle": 30},
+            "productions": [
+                "B < A(x) > [B]: x > 1 -> A(x - 1)[+(angle)B]",
+                "0.5; A(x) -> B",
+                "0.5; A(x) -> A(x)"
+            ],
+            "axiom": "A(2 * angle)B"
+        }))
+        .unwrap();
+        assert_eq!(parsed.axiom().to_string(), "A(60)B");
+        let productions = &parsed.productions()["A"];
+        assert_eq!(productions.len(), 2);
+        // context-sensitive productions are more specific and tried first
+        assert_eq!(productions[0].left_context().len(), 1);
+        assert_eq!(productions[0].right_context().len(), 3);
+        assert!(productions[0].condition().is_some());
+        // productions with the same requirements form one stochastic production
+        assert_eq!(productions[1].successors().len(), 2);
+        assert_eq!(productions[1].successors()[1].probability(), 0.5);
+    }
+
+    #[test]
+    fn matches_longest_symbol_names_first() {
+        let parsed = parse(json!({
+            "alphabet": ["F", "FF", "F(l)"],
+            "parameters": {},
+            "axiom": "FFF(1)F"
+        }))
+        .unwrap();
+        assert_eq!(parsed.axiom().len(), 3);
+        assert_eq!(parsed.axiom().name(0), "FF");
+        assert_eq!(parsed.axiom().name(1), "F");
+        assert_eq!(parsed.axiom().parameters(1), &[1.]);
+    }
+
+    #[test]
+    fn reports_unknown_modules() {
+        let error = parse_error(json!({
+            "alphabet": ["A", "B"],
+            "parameters": {},
+            "productions": ["A -> B", "B -> AC"],
+            "axiom": "A"
+        }));
+        assert_eq!(error.location(), &ParseErrorLocation::Production(1));
+        assert_eq!(error.source(), "B -> AC");
+        assert_eq!(error.column(), 7);
+        assert_eq!(error.snippet(), "B -> AC\n      ^");
+    }
+
+    #[test]
+    fn reports_expression_errors_at_their_column() {
+        let error = parse_error(json!({
+            "alphabet": ["A(x)"],
+            "parameters": {},
+            "productions": ["A(x) -> A(x + y)"],
+            "axiom": "A(1)"
+        }));
+        assert_eq!(error.location(), &ParseErrorLocation::Production(0));
+        assert_eq!(error.column(), 15);
+
+        let error = parse_error(json!({
+            "alphabet": ["A(x)"],
+            "parameters": {},
+            "axiom": "A(1 +)"
+        }));
+        assert_eq!(error.location(), &ParseErrorLocation::Axiom);
+        assert_eq!(error.column(), 6);
+    }
+
+    #[test]
+    fn reports_missing_successor() {
+        let error = parse_error(json!({
+            "alphabet": ["A"],
+            "parameters": {},
+            "tables": {"grow": ["A"]},
+            "axiom": "A"
+        }));
+        assert_eq!(
+            error.location(),
+            &ParseErrorLocation::TableProduction("grow".to_string(), 0)
+        );
+        assert_eq!(error.column(), 2);
+    }
+
+    #[test]
+    fn reports_invalid_declarations() {
+        let error = parse_error(json!({
+            "alphabet": ["A(x)", "B", "["],
+            "parameters": {},
+            "productions": ["[B < A(x) -> A(x)"],
+            "axiom": "A(1)"
+        }));
+        assert_eq!(error.column(), 1);
+        assert!(error.message().contains("Branches"));
+
+        let error = parse_error(json!({
+            "alphabet": ["A(x)", "B(x)"],
+            "parameters": {},
+            "productions": ["A(x) > B(x) -> A(x)"],
+            "axiom": "A(1)"
+        }));
+        assert_eq!(error.column(), 10);
+        assert!(error.message().contains("Duplicate"));
+
+        let error = parse_error(json!({
+            "alphabet": ["A"],
+            "parameters": {},
+            "schedule": ["missing"],
+            "axiom": "A"
+        }));
+        assert_eq!(error.location(), &ParseErrorLocation::Schedule(0));
+    }
+
+    #[test]
+    fn serializes_errors_for_the_user_interface() {
+        let error = parse_error(json!({
+            "alphabet": ["A"],
+            "parameters": {},
+            "productions": ["A -> B"],
+            "axiom": "A"
+        }));
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({
+                "location": {"type": "production", "index": 0},
+                "source": "A -> B",
+                "column": 6,
+                "message": error.message(),
+                "snippet": "A -> B\n     ^"
+            })
+        );
+    }
+}
@@ -0,0 +1,435 @@
+use crate::lindenmayer::expression::{EvaluationError, Expression};
+use crate::lindenmayer::module::Symbol;
+use crate::lindenmayer::module_string::ModuleString;
+use crate::lindenmayer::random::Random;
+use std::collections::HashSet;
+
+pub const BRANCH_START: &str = "[";
+pub const BRANCH_END: &str = "]";
+
+// skips the branch starting at `index` and returns the index of the module following it
+fn skip_branch_forward(modules: &ModuleString, index: usize) -> Option<usize> {
+    let mut depth = 0;
+    for i in index..modules.len() {
+        match modules.get(i)?.name() {
+            BRANCH_START => depth += 1,
+            BRANCH_END => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// skips the remainder of the branch containing `index` and returns the index of the module
+// following the end of the branch
+fn skip_to_branch_end(modules: &ModuleString, index: usize) -> Option<usize> {
+    let mut depth = 0;
+    for i in index..modules.len() {
+        match modules.get(i)?.name() {
+            BRANCH_START => depth += 1,
+            BRANCH_END => {
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+                depth -= 1;
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Finds the modules matching a left context for the module at `index` and appends their
+/// parameters in the order of the context to `bound_parameters`.
+/// The left context is searched on the path from the module towards the root of the branching
+/// structure, i.e., branches are skipped and the start of a branch leads to its parent.
+/// Returns `None` if the context doesn't match, in which case `bound_parameters` may contain the
+/// parameters of a partial match.
+fn match_left_context(
+    modules: &ModuleString,
+    index: usize,
+    context: &[Symbol],
+    ignored: &HashSet<String>,
+    bound_parameters: &mut Vec<f32>,
+) -> Option<()> {
+    // modules are matched backwards, so each one's parameters go before those matched so far
+    let start = bound_parameters.len();
+    let mut remaining = context.len();
+    let mut i = index;
+    while remaining > 0 {
+        if i == 0 {
+            return None;
+        }
+        i -= 1;
+        let module = modules.get(i)?;
+        match module.name() {
+            BRANCH_END => {
+                // skip the whole branch, s.t. `i` points to its start
+                let mut depth = 1;
+                while depth > 0 {
+                    if i == 0 {
+                        return None;
+                    }
+                    i -= 1;
+                    match modules.name(i) {
+                        BRANCH_END => depth += 1,
+                        BRANCH_START => depth -= 1,
+                        _ => {}
+                    }
+                }
+            }
+            BRANCH_START => {}
+            name if ignored.contains(name) => {}
+            _ => {
+                if !module.matches(&context[remaining - 1]) {
+                    return None;
+                }
+                remaining -= 1;
+                let parameters = modules.parameters(i);
+                bound_parameters.splice(start..start, parameters.iter().copied());
+            }
+        }
+    }
+    Some(())
+}
+
+/// Finds the modules matching a right context for the module at `index` and appends their
+/// parameters in the order of the context to `bound_parameters`.
+/// Branches in the module string are skipped unless the context starts a branch as well, and a
+/// branch end in the context skips the remainder of the current branch.
+/// Returns `None` if the context doesn't match, in which case `bound_parameters` may contain the
+/// parameters of a partial match.
+fn match_right_context(
+    modules: &ModuleString,
+    index: usize,
+    context: &[Symbol],
+    ignored: &HashSet<String>,
+    bound_parameters: &mut Vec<f32>,
+) -> Option<()> {
+    let mut i = index + 1;
+    let mut c = 0;
+    while c < context.len() {
+        let symbol = &context[c];
+        if symbol.name() == BRANCH_END {
+            i = skip_to_branch_end(modules, i)?;
+            c += 1;
+            continue;
+        }
+        let module = modules.get(i)?;
+        if module.matches(symbol) {
+            bound_parameters.extend_from_slice(modules.parameters(i));
+            i += 1;
+            c += 1;
+            continue;
+        }
+        match module.name() {
+            BRANCH_START => i = skip_branch_forward(modules, i)?,
+            BRANCH_END => return None,
+            name if ignored.contains(name) => i += 1,
+            _ => return None,
+        }
+    }
+    Some(())
+}
+
+/// A parameter of a module form, where strings are interned in the L-system's `SymbolTable`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SuccessorParameter {
+    Expression(Expression),
+    String(u32),
+}
+
+/// A module form in a production's body, i.e., an interned module name and expressions to
+/// compute the parameters of the module that is created when the production is applied.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SuccessorModule {
+    symbol: u32,
+    parameters: Vec<SuccessorParameter>,
+}
+
+impl SuccessorModule {
+    pub fn new(symbol: u32, parameters: Vec<SuccessorParameter>) -> Self {
+        Self { symbol, parameters }
+    }
+
+    /// Appends the module created from this module form to the given module string.
+    pub fn instantiate_into(
+        &self,
+        bound_parameters: &[f32],
+        random: &mut Random,
+        target: &mut ModuleString,
+    ) -> Result<(), EvaluationError> {
+        target.push_module(self.symbol);
+        for parameter in self.parameters.iter() {
+            match parameter {
+                SuccessorParameter::Expression(expression) => {
+                    target.push_parameter(expression.evaluate(bound_parameters, random)?)
+                }
+                SuccessorParameter::String(string) => target.push_string_parameter(*string),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One of possibly multiple bodies of a production and its probability to be chosen.
+#[derive(Clone, Debug)]
+pub struct Successor {
+    index: usize,
+    probability: f32,
+    modules: Vec<SuccessorModule>,
+}
+
+impl Successor {
+    pub fn new(index: usize, probability: f32, modules: Vec<SuccessorModule>) -> Self {
+        Self {
+            index,
+            probability,
+            modules,
+        }
+    }
+    /// The index of the production this successor was defined in within the list of productions
+    /// (or the table) it belongs to.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+    pub fn probability(&self) -> f32 {
+        self.probability
+    }
+    pub fn modules(&self) -> &Vec<SuccessorModule> {
+        &self.modules
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Production {
+    symbol: Symbol,
+    left_context: Vec<Symbol>,
+    right_context: Vec<Symbol>,
+    condition: Option<Expression>,
+    successors: Vec<Successor>,
+}
+
+impl Production {
+    pub fn new(
+        symbol: Symbol,
+        left_context: Vec<Symbol>,
+        right_context: Vec<Symbol>,
+        condition: Option<Expression>,
+        successors: Vec<Successor>,
+    ) -> Self {
+        Self {
+            symbol,
+            left_context,
+            right_context,
+            condition,
+            successors,
+        }
+    }
+
+    pub fn symbol(&self) -> &Symbol {
+        &self.symbol
+    }
+    pub fn left_context(&self) -> &Vec<Symbol> {
+        &self.left_context
+    }
+    pub fn right_context(&self) -> &Vec<Symbol> {
+        &self.right_context
+    }
+    pub fn condition(&self) -> &Option<Expression> {
+        &self.condition
+    }
+    pub fn successors(&self) -> &Vec<Successor> {
+        &self.successors
+    }
+
+    /// Productions with a larger environment or a condition are more specific than others and are
+    /// therefore checked first.
+    pub fn rank(&self) -> usize {
+        self.left_context.len()
+            + self.right_context.len()
+            + if self.condition.is_some() { 1 } else { 0 }
+    }
+
+    /// Checks if this production's requirements are met for the module at the given index in the
+    /// given module string.
+    /// Modules in `ignored` are skipped when searching for the production's context.
+    /// If the requirements are met, `bound_parameters` is set to the parameters bound by the
+    /// production's module declaration (module parameters, followed by the parameters of the left
+    /// and right context) and true is returned.
+    pub fn try_match(
+        &self,
+        modules: &ModuleString,
+        index: usize,
+        ignored: &HashSet<String>,
+        random: &mut Random,
+        bound_parameters: &mut Vec<f32>,
+    ) -> Result<bool, EvaluationError> {
+        match modules.get(index) {
+            Some(module) if module.matches(&self.symbol) => {}
+            _ => return Ok(false),
+        }
+        bound_parameters.clear();
+        bound_parameters.extend_from_slice(modules.parameters(index));
+        if match_left_context(
+            modules,
+            index,
+            &self.left_context,
+            ignored,
+            bound_parameters,
+        )
+        .is_none()
+            || match_right_context(
+                modules,
+                index,
+                &self.right_context,
+                ignored,
+                bound_parameters,
+            )
+            .is_none()
+        {
+            return Ok(false);
+        }
+
+        if let Some(condition) = &self.condition {
+            if !condition.evaluate_condition(bound_parameters, random)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn choose_successor(&self, random: &mut Random) -> &Successor {
+        if self.successors.len() == 1 {
+            return &self.successors[0];
+        }
+        // probabilities are rescaled if they don't sum up to 1
+        let total: f32 = self.successors.iter().map(|s| s.probability).sum();
+        let mut choice = random.next_f32() * total;
+        for successor in self.successors.iter() {
+            if choice < successor.probability {
+                return successor;
+            }
+            choice -= successor.probability;
+        }
+        self.successors.last().unwrap()
+    }
+
+    /// Chooses a successor and appends its instantiated modules to the given module string.
+    /// Returns the index of the chosen successor's definition (see `Successor::index`).
+    pub fn apply_into(
+        &self,
+        bound_parameters: &[f32],
+        random: &mut Random,
+        target: &mut ModuleString,
+    ) -> Result<usize, EvaluationError> {
+        let successor = self.choose_successor(random);
+        for module in successor.modules.iter() {
+            module.instantiate_into(bound_parameters, random, target)?;
+        }
+        Ok(successor.index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lindenmayer::{LSystem, LSystemDefinition};
+    use serde_json::json;
+
+    fn derive_once(
+        alphabet: &[&str],
+        ignore: &[&str],
+        productions: &[&str],
+        axiom: &str,
+    ) -> String {
+        let definition: LSystemDefinition = serde_json::from_value(json!({
+            "alphabet": alphabet,
+            "parameters": {},
+            "productions": productions,
+            "ignore": ignore,
+            "axiom": axiom
+        }))
+        .unwrap();
+        let mut l_system = LSystem::new(&definition).unwrap();
+        l_system.next_modules().unwrap().to_string()
+    }
+
+    #[test]
+    fn matches_left_context() {
+        let alphabet = ["A", "B", "C"];
+        assert_eq!(derive_once(&alphabet, &[], &["A < B -> A"], "ABBB"), "AABB");
+        assert_eq!(derive_once(&alphabet, &[], &["A < B -> C"], "CBAB"), "CBAC");
+        assert_eq!(
+            derive_once(&alphabet, &[], &["A B < C -> A"], "ABCBC"),
+            "ABABC"
+        );
+    }
+
+    #[test]
+    fn searches_left_context_towards_the_root() {
+        let alphabet = ["A", "B", "C", "[", "]"];
+        // the start of a branch leads to its parent and preceding branches are skipped
+        assert_eq!(
+            derive_once(&alphabet, &[], &["A < B -> C"], "A[B][CB]B[[B]]"),
+            "A[C][CB]C[[B]]"
+        );
+        assert_eq!(
+            derive_once(&alphabet, &[], &["A < B -> C"], "A[[B]]"),
+            "A[[C]]"
+        );
+    }
+
+    #[test]
+    fn matches_right_context_across_branches() {
+        let alphabet = ["A", "B", "C", "[", "]"];
+        // branches in the module string are skipped
+        assert_eq!(
+            derive_once(&alphabet, &[], &["A > B -> C"], "A[C]BAC"),
+            "C[C]BAC"
+        );
+        // branches in the context must match branches in the module string
+        assert_eq!(
+            derive_once(&alphabet, &[], &["A > [B]C -> C"], "A[B]CA[C]C"),
+            "C[B]CA[C]C"
+        );
+        // a branch end in the context skips the remainder of the current branch
+        assert_eq!(
+            derive_once(&alphabet, &[], &["A > ]B -> C"], "[AC]B[A]C"),
+            "[CC]B[A]C"
+        );
+        // the right context doesn't leave the current branch
+        assert_eq!(derive_once(&alphabet, &[], &["A > B -> C"], "[A]B"), "[A]B");
+    }
+
+    #[test]
+    fn skips_ignored_modules() {
+        let alphabet = ["A", "B", "+", "-"];
+        assert_eq!(
+            derive_once(&alphabet, &["+", "-"], &["A < B > A -> A"], "A+B-+A"),
+            "A+A-+A"
+        );
+        assert_eq!(
+            derive_once(&alphabet, &["+"], &["A < B -> A"], "A-B"),
+            "A-B"
+        );
+    }
+
+    #[test]
+    fn binds_context_parameters_in_order() {
+        let alphabet = ["A(x)", "B(x)", "C(x, y)"];
+        assert_eq!(
+            derive_once(
+                &alphabet,
+                &[],
+                &["A(a) < B(b) > C(c, d) -> B(a * 1000 + b * 100 + c * 10 + d)"],
+                "A(1)B(2)C(3,4)"
+            ),
+            "A(1)B(1234)C(3,4)"
+        );
+    }
+}
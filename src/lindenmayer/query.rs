@@ -0,0 +1,27 @@
+/// A query module whose parameters are set to the turtle's position when the module string is
+/// interpreted, e.g., `?P(x,y,z)`.
+pub const POSITION_QUERY: &str = "?P";
+
+/// A query module whose parameters are set to the turtle's heading when the module string is
+/// interpreted, e.g., `?H(x,y,z)`.
+pub const HEADING_QUERY: &str = "?H";
+
+/// A communication module whose parameters are set by the L-system's environment when the module
+/// string is interpreted, e.g., `?E(light)`.
+pub const ENVIRONMENT_QUERY: &str = "?E";
+
+/// The number of parameters of the `?P` and `?H` query modules.
+pub const NUM_QUERY_PARAMETERS: usize = 3;
+
+pub fn is_query_module(name: &str) -> bool {
+    matches!(name, POSITION_QUERY | HEADING_QUERY | ENVIRONMENT_QUERY)
+}
+
+/// Returns the number of parameters a query module must have or `None` if it may have any number
+/// of parameters.
+pub fn num_query_parameters(name: &str) -> Option<usize> {
+    match name {
+        POSITION_QUERY | HEADING_QUERY => Some(NUM_QUERY_PARAMETERS),
+        _ => None,
+    }
+}
@@ -0,0 +1,31 @@
+/// A small pseudo random number generator (SplitMix64) used for stochastic productions and
+/// `Math.random()` calls in expressions.
+/// It only relies on integer arithmetic, so it produces the same sequence on every platform.
+#[derive(Clone, Debug)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn from_time() -> Self {
+        Self::new(instant::now().to_bits())
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a uniformly distributed number in `[0;1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        // use the upper 24 bits, i.e., the number of bits in an f32's mantissa
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
@@ -0,0 +1,239 @@
+use crate::lindenmayer::encoding::{Decoder, Encoder};
+use crate::lindenmayer::module_string::{ModuleString, SymbolTable};
+use std::collections::HashMap;
+use std::mem::size_of;
+use std::sync::Arc;
+
+/// A production that was applied in a derivation step.
+#[derive(Copy, Clone, Debug)]
+pub struct AppliedProduction {
+    iteration: usize,
+    index: usize,
+}
+
+impl AppliedProduction {
+    pub fn new(iteration: usize, index: usize) -> Self {
+        Self { iteration, index }
+    }
+    /// The iteration the production produced (see `LSystem::table` for the table it belongs to).
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+    /// The index of the production in the list of productions (or the table) it was defined in.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+// marks an absent value in the fields of a module's history
+const NONE: u32 = u32::MAX;
+
+fn optional(value: u32) -> Option<u32> {
+    (value != NONE).then_some(value)
+}
+
+/// The developmental history of a module in a module string.
+///
+/// Its fields are packed into `u32`s, where `u32::MAX` stands for `None`, s.t. the history of
+/// large module strings stays compact and can be stored as is.
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct ModuleHistory {
+    id: u32,
+    birth: u32,
+    parent: u32,
+    production_iteration: u32,
+    production_index: u32,
+    predecessor: u32,
+}
+
+impl ModuleHistory {
+    /// Creates the history of a module in the axiom.
+    pub fn axiom(id: u64) -> Self {
+        Self {
+            id: id as u32,
+            birth: 0,
+            parent: NONE,
+            production_iteration: NONE,
+            production_index: NONE,
+            predecessor: NONE,
+        }
+    }
+
+    /// Creates the history of a module that was created by applying a production to the module
+    /// with the given history.
+    pub fn produced(id: u64, birth: usize, parent: &Self, production: AppliedProduction) -> Self {
+        Self {
+            id: id as u32,
+            birth: birth as u32,
+            parent: parent.id,
+            production_iteration: production.iteration as u32,
+            production_index: production.index as u32,
+            predecessor: NONE,
+        }
+    }
+
+    /// Creates the history of a module that continues its predecessor at the given index in the
+    /// previous module string after a production was applied to it.
+    pub fn continued(&self, production: AppliedProduction, predecessor: usize) -> Self {
+        Self {
+            production_iteration: production.iteration as u32,
+            production_index: production.index as u32,
+            predecessor: predecessor as u32,
+            ..*self
+        }
+    }
+
+    /// Creates the history of a module that was copied because no production was applied to it.
+    pub fn copied(&self) -> Self {
+        Self {
+            predecessor: NONE,
+            ..*self
+        }
+    }
+
+    /// Shifts the module's lineage, e.g., to keep lineages unique when parts of a module string are
+    /// derived independently.
+    pub fn offset_id(&mut self, offset: u64) {
+        self.id += offset as u32;
+    }
+
+    /// The module's lineage, which is shared by all modules continuing it in later iterations.
+    pub fn id(&self) -> u64 {
+        self.id as u64
+    }
+    /// The iteration the module was created in.
+    pub fn birth(&self) -> usize {
+        self.birth as usize
+    }
+    /// The lineage of the module that was rewritten to create this module's lineage or `None` if
+    /// its lineage starts in the axiom.
+    pub fn parent(&self) -> Option<u64> {
+        optional(self.parent).map(|parent| parent as u64)
+    }
+    /// The production that most recently produced the module or `None` if the module has never
+    /// been rewritten.
+    pub fn production(&self) -> Option<AppliedProduction> {
+        optional(self.production_iteration)
+            .zip(optional(self.production_index))
+            .map(|(iteration, index)| AppliedProduction::new(iteration as usize, index as usize))
+    }
+    /// The index of the module's predecessor in the previous module string if the module
+    /// continues its predecessor, i.e., it is the first module with the same name and number of
+    /// parameters in the successor of the production that replaced its predecessor.
+    pub fn predecessor(&self) -> Option<usize> {
+        optional(self.predecessor).map(|predecessor| predecessor as usize)
+    }
+}
+
+/// A module string of a timed L-system together with the history of its modules.
+/// Iteration `n` develops continuously from time `n - 1` to time `n`.
+#[derive(Clone, Debug)]
+pub struct TimedSnapshot {
+    iteration: usize,
+    modules: ModuleString,
+    history: Vec<ModuleHistory>,
+}
+
+impl TimedSnapshot {
+    pub fn new(iteration: usize, modules: ModuleString, history: Vec<ModuleHistory>) -> Self {
+        Self {
+            iteration,
+            modules,
+            history,
+        }
+    }
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+    pub fn modules(&self) -> &ModuleString {
+        &self.modules
+    }
+    pub fn history(&self) -> &Vec<ModuleHistory> {
+        &self.history
+    }
+
+    pub fn into_parts(self) -> (usize, ModuleString, Vec<ModuleHistory>) {
+        (self.iteration, self.modules, self.history)
+    }
+
+    /// The number of bytes occupied by the snapshot's modules and their history.
+    pub fn memory_usage(&self) -> usize {
+        self.modules.memory_usage() + self.history.len() * size_of::<ModuleHistory>()
+    }
+
+    /// Writes the snapshot into a binary blob.
+    pub fn encode(&self, encoder: &mut Encoder) {
+        encoder.u64(self.iteration as u64);
+        self.modules.encode(encoder);
+        encoder.slice(&self.history);
+    }
+
+    /// Reads a snapshot written by `encode` (see `ModuleString::decode`), whose previous module
+    /// string has the given number of modules.
+    /// Returns `None` if a module's predecessor is not part of the previous module string.
+    pub fn decode(
+        decoder: &mut Decoder,
+        symbols: &Arc<SymbolTable>,
+        previous_len: usize,
+    ) -> Option<Self> {
+        let iteration = decoder.usize()?;
+        let modules = ModuleString::decode(decoder, symbols)?;
+        let history = decoder.slice::<ModuleHistory>()?;
+        let valid = history.len() == modules.len()
+            && history
+                .iter()
+                .all(|h| h.predecessor().is_none_or(|p| p < previous_len));
+        valid.then_some(Self {
+            iteration,
+            modules,
+            history,
+        })
+    }
+
+    /// Returns the module string at the given fraction of the time between the previous iteration
+    /// and this one, where numeric parameters of modules continuing their predecessor in the
+    /// given previous module string are linearly interpolated.
+    pub fn interpolated_modules(&self, previous: &ModuleString, fraction: f32) -> ModuleString {
+        let fraction = fraction.clamp(0., 1.);
+        let mut modules = self.modules.clone();
+        if fraction < 1. {
+            for (i, history) in self.history.iter().enumerate() {
+                // predecessors beyond the previous module string are ignored, e.g., if it is not the
+                // module string this one was derived from
+                if let Some(predecessor) = history
+                    .predecessor()
+                    .filter(|&predecessor| predecessor < previous.len())
+                {
+                    // string parameters are NaN and therefore kept
+                    for (current, previous) in modules
+                        .parameters_mut(i)
+                        .iter_mut()
+                        .zip(previous.parameters(predecessor))
+                    {
+                        if !current.is_nan() && !previous.is_nan() {
+                            *current = previous + (*current - previous) * fraction;
+                        }
+                    }
+                }
+            }
+        }
+        modules
+    }
+
+    /// Returns the growth of the module at the given index in `[0;1]` at the given fraction of the
+    /// time between the previous iteration and this one, i.e., the module's age divided by its
+    /// terminal age.
+    /// Modules without a terminal age grow within a single time unit.
+    pub fn growth(&self, index: usize, fraction: f32, terminal_ages: &HashMap<String, f32>) -> f32 {
+        let birth = self.history[index].birth();
+        // a module born in iteration `b` starts to grow at time `b - 1`
+        let age = self.iteration as f32 - birth as f32 + fraction.clamp(0., 1.);
+        let terminal_age = terminal_ages
+            .get(self.modules.name(index))
+            .copied()
+            .unwrap_or(1.)
+            .max(f32::EPSILON);
+        (age / terminal_age).clamp(0., 1.)
+    }
+}
@@ -1,7 +1,8 @@
 use crate::lindenmayer::LSystemDefinition;
-use crate::LSystemSceneDescriptor;
+use crate::lsystemrenderer::l_system_manager::evaluator::EvaluationProgress;
+use crate::lsystemrenderer::scene_descriptor::LSystemSceneDescriptor;
 use glam::Vec3;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 #[derive(Clone, Debug, Deserialize)]
@@ -20,10 +21,76 @@ impl IterationEvent {
     }
 }
 
+/// A byte buffer that is passed to and from the page as a `Uint8Array` instead of an array of
+/// numbers.
+#[cfg(target_arch = "wasm32")]
+#[derive(Clone, Debug)]
+pub struct Uint8Array(Vec<u8>);
+
+#[cfg(target_arch = "wasm32")]
+impl Serialize for Uint8Array {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl<'de> Deserialize<'de> for Uint8Array {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Uint8ArrayVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for Uint8ArrayVisitor {
+            type Value = Uint8Array;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a Uint8Array")
+            }
+            fn visit_byte_buf<E: serde::de::Error>(self, bytes: Vec<u8>) -> Result<Uint8Array, E> {
+                Ok(Uint8Array(bytes))
+            }
+            fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<Uint8Array, E> {
+                Ok(Uint8Array(bytes.to_vec()))
+            }
+        }
+
+        deserializer.deserialize_byte_buf(Uint8ArrayVisitor)
+    }
+}
+
+/// An entry of the cache of evaluated iterations, which is stored by the page on the web.
+///
+/// The app requests entries with `data` set to `None`, and the page responds with the stored
+/// data, or `None` if there is no entry with the requested key.
+#[cfg(target_arch = "wasm32")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CacheEvent {
+    key: String,
+    data: Option<Uint8Array>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl CacheEvent {
+    pub fn new(key: String, data: Option<Vec<u8>>) -> Self {
+        Self {
+            key,
+            data: data.map(Uint8Array),
+        }
+    }
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+    pub fn data(&self) -> Option<&Vec<u8>> {
+        self.data.as_ref().map(|data| &data.0)
+    }
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub enum LSystemEvent {
     #[serde(rename = "iteration")]
     Iteration(IterationEvent),
+    #[cfg(target_arch = "wasm32")]
+    #[serde(rename = "cache")]
+    Cache(CacheEvent),
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -59,3 +126,52 @@ pub enum UiEvent {
     #[serde(rename = "scene")]
     Scene(SceneEvent),
 }
+
+/// Reports the progress of evaluating the iterations of an L-system instance to the UI.
+/// Objects overriding the instance's seed or parameters are evaluated separately, so the progress
+/// is reported for the names of the objects sharing the evaluation.
+#[derive(Clone, Debug, Serialize)]
+pub struct EvaluationProgressEvent {
+    system: String,
+    instance: String,
+    objects: Vec<String>,
+    iteration: u32,
+    #[serde(rename = "targetIteration")]
+    target_iteration: u32,
+    #[serde(rename = "modulesProcessed")]
+    modules_processed: usize,
+    #[serde(rename = "estimatedModulesRemaining")]
+    estimated_modules_remaining: usize,
+}
+
+impl EvaluationProgressEvent {
+    pub fn new(
+        system: &str,
+        instance: &str,
+        objects: Vec<String>,
+        progress: &EvaluationProgress,
+    ) -> Self {
+        Self {
+            system: system.to_string(),
+            instance: instance.to_string(),
+            objects,
+            iteration: progress.iteration(),
+            target_iteration: progress.target_iteration(),
+            modules_processed: progress.modules_processed(),
+            estimated_modules_remaining: progress.estimated_modules_remaining(),
+        }
+    }
+}
+
+/// Reports the memory occupied by evaluated L-system iterations and animations to the UI.
+#[derive(Copy, Clone, Debug, Serialize)]
+pub struct MemoryUsageEvent {
+    usage: usize,
+    budget: usize,
+}
+
+impl MemoryUsageEvent {
+    pub fn new(usage: usize, budget: usize) -> Self {
+        Self { usage, budget }
+    }
+}
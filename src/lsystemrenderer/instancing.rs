@@ -1,3 +1,4 @@
+use crate::lindenmayer::timed::ModuleHistory;
 use glam::{Mat4, Vec3, Vec4};
 use serde::Deserialize;
 
@@ -9,7 +10,7 @@ struct SerializedMaterial {
 }
 
 #[repr(C)]
-#[derive(Copy, Clone, Debug, Deserialize, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, bytemuck::Pod, bytemuck::Zeroable)]
 #[serde(from = "SerializedMaterial")]
 pub struct Material {
     albedo: Vec4,
@@ -69,11 +70,68 @@ impl ModelTransform {
     }
 }
 
+/// The identity of the module an instance was created from.
+/// Ids are truncated to 32 bits, unknown ids are `u32::MAX`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Lineage {
+    id: u32,
+    parent: u32,
+    production: u32,
+    iteration: u32,
+}
+
+impl Lineage {
+    pub const UNKNOWN: Lineage = Lineage {
+        id: u32::MAX,
+        parent: u32::MAX,
+        production: u32::MAX,
+        iteration: u32::MAX,
+    };
+
+    /// The lineage of the module (see `ModuleHistory::id`).
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+    /// The lineage of the module the module's lineage was created from.
+    pub fn parent(&self) -> u32 {
+        self.parent
+    }
+    /// The index of the production that most recently produced the module.
+    pub fn production(&self) -> u32 {
+        self.production
+    }
+    /// The iteration the production most recently producing the module was applied in, i.e., the
+    /// one that determines the table the production belongs to.
+    pub fn iteration(&self) -> u32 {
+        self.iteration
+    }
+}
+
+impl Default for Lineage {
+    fn default() -> Self {
+        Self::UNKNOWN
+    }
+}
+
+impl From<&ModuleHistory> for Lineage {
+    fn from(history: &ModuleHistory) -> Self {
+        let production = history.production();
+        Self {
+            id: history.id() as u32,
+            parent: history.parent().map_or(u32::MAX, |p| p as u32),
+            production: production.map_or(u32::MAX, |p| p.index() as u32),
+            iteration: production.map_or(u32::MAX, |p| p.iteration() as u32),
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Instance {
     transform: ModelTransform,
     material: Material,
+    lineage: Lineage,
 }
 
 impl Instance {
@@ -81,6 +139,7 @@ impl Instance {
         Self {
             transform: ModelTransform::new(matrix),
             material,
+            lineage: Lineage::UNKNOWN,
         }
     }
     pub fn matrix(&self) -> Mat4 {
@@ -98,6 +157,13 @@ impl Instance {
     pub fn set_material(&mut self, material: Material) {
         self.material = material;
     }
+    /// The identity of the module the instance was created from.
+    pub fn lineage(&self) -> Lineage {
+        self.lineage
+    }
+    pub fn set_lineage(&mut self, lineage: Lineage) {
+        self.lineage = lineage;
+    }
 }
 
 impl Default for Instance {
@@ -0,0 +1,145 @@
+use crate::lindenmayer::encoding::stable_hash;
+use crate::lindenmayer::LSystem;
+use crate::lsystemrenderer::l_system_manager::evaluator::Interpreter;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::SystemTime;
+
+/// Changing the layout of cached iterations invalidates all existing entries.
+const CACHE_VERSION: u32 = 4;
+
+/// The environment variable that overrides the directory iterations are cached in on native.
+#[cfg(not(target_arch = "wasm32"))]
+pub const CACHE_DIRECTORY_VARIABLE: &str = "L_SYSTEM_CACHE_DIR";
+
+/// The environment variable that overrides the size limit of the cache directory in megabytes on
+/// native.
+#[cfg(not(target_arch = "wasm32"))]
+pub const CACHE_SIZE_VARIABLE: &str = "L_SYSTEM_CACHE_SIZE";
+
+/// The default size limit of the cache directory in megabytes on native.
+#[cfg(not(target_arch = "wasm32"))]
+const DEFAULT_CACHE_SIZE: u64 = 1024;
+
+/// Identifies the persisted iterations of an L-system instance.
+///
+/// Entries are keyed by a hash of the L-system's definition (including its instance parameters),
+/// its seed and the settings of its interpreter, s.t. changing any of them invalidates them.
+/// On native, entries are files in a cache directory, whose least recently used entries are
+/// removed once it exceeds its size limit. On the web, they are stored by the page (see
+/// `IterationWorker::receive_cache`).
+#[derive(Clone, Debug)]
+pub struct IterationCache {
+    key: String,
+    #[cfg(not(target_arch = "wasm32"))]
+    directory: PathBuf,
+    // the size limit of the cache directory in bytes
+    #[cfg(not(target_arch = "wasm32"))]
+    max_size: u64,
+}
+
+impl IterationCache {
+    pub fn new(l_system: &LSystem, interpreter: &Interpreter) -> Self {
+        // serialized maps are sorted by their keys, so equal definitions have equal hashes
+        let definition = serde_json::to_value(l_system.definition())
+            .map(|definition| definition.to_string())
+            .unwrap_or_default();
+        let description = format!(
+            "{}\n{}\n{}\n{}",
+            CACHE_VERSION,
+            definition,
+            l_system.seed(),
+            interpreter.fingerprint()
+        );
+        Self {
+            key: format!("{:016x}", stable_hash(description.as_bytes())),
+            #[cfg(not(target_arch = "wasm32"))]
+            directory: std::env::var_os(CACHE_DIRECTORY_VARIABLE)
+                .map(PathBuf::from)
+                .unwrap_or_else(|| std::env::temp_dir().join("parametric-l-systems")),
+            #[cfg(not(target_arch = "wasm32"))]
+            max_size: std::env::var(CACHE_SIZE_VARIABLE)
+                .ok()
+                .and_then(|size| size.parse::<u64>().ok())
+                .unwrap_or(DEFAULT_CACHE_SIZE)
+                .saturating_mul(1024 * 1024),
+        }
+    }
+
+    /// The key of the entry storing the given iteration.
+    pub fn entry_key(&self, iteration: usize) -> String {
+        format!("{}-{}", self.key, iteration)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl IterationCache {
+    fn entry_path(&self, iteration: usize) -> PathBuf {
+        self.directory
+            .join(format!("{}.bin", self.entry_key(iteration)))
+    }
+
+    /// Returns the persisted entry of the given iteration if there is one.
+    pub fn load(&self, iteration: usize) -> Option<Vec<u8>> {
+        let path = self.entry_path(iteration);
+        let data = std::fs::read(&path).ok()?;
+        // entries are evicted by their modification time, so it is updated on each use
+        std::fs::File::options()
+            .append(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()))
+            .ok();
+        Some(data)
+    }
+
+    /// Persists the entry of the given iteration.
+    pub fn store(&self, iteration: usize, data: &[u8]) {
+        // entries are written to a temporary file first, s.t. other instances never read partial
+        // entries
+        let path = self.entry_path(iteration);
+        let temporary = path.with_extension(format!("{}.tmp", std::process::id()));
+        let stored = std::fs::create_dir_all(&self.directory)
+            .and_then(|_| std::fs::write(&temporary, data))
+            .and_then(|_| std::fs::rename(&temporary, &path));
+        if let Err(error) = stored {
+            log::error!("Could not cache iteration in {:?}: {}", path, error);
+            std::fs::remove_file(&temporary).ok();
+        }
+        self.evict();
+    }
+
+    // removes the least recently used entries of all L-systems until the cache directory fits
+    // into its size limit
+    fn evict(&self) {
+        let entries = match std::fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        let mut entries: Vec<(PathBuf, u64, SystemTime)> = entries
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let metadata = entry.metadata().ok()?;
+                let path = entry.path();
+                (metadata.is_file() && path.extension().is_some_and(|e| e == "bin")).then(|| {
+                    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                    (path, metadata.len(), modified)
+                })
+            })
+            .collect();
+        let mut size: u64 = entries.iter().map(|(_, len, _)| len).sum();
+        if size <= self.max_size {
+            return;
+        }
+        entries.sort_by_key(|(_, _, modified)| *modified);
+        for (path, len, _) in entries {
+            if size <= self.max_size {
+                break;
+            }
+            // entries may have been removed by other instances in the meantime
+            if std::fs::remove_file(&path).is_ok() {
+                size -= len;
+            }
+        }
+    }
+}
@@ -1,14 +1,17 @@
-use serde::Deserialize;
+use crate::lindenmayer::module_string::ModuleRef;
+use crate::lindenmayer::query::is_query_module;
+use glam::Vec3;
+use std::borrow::Cow;
 
-// F(d,r)
+// F(d,r), F(d,r,r1)
 // Positioniere einen Zylinder der Länge d mit Radius r entlang der h-Achse.
 // Translation entlang der h-Achse um die Länge d (an den Endpunkt des Zylinders).
-#[derive(Debug, Deserialize)]
-pub struct AddCylinder {
-    parameters: Vec<f32>,
+#[derive(Debug)]
+pub struct AddCylinder<'a> {
+    parameters: Cow<'a, [f32]>,
 }
 
-impl AddCylinder {
+impl<'a> AddCylinder<'a> {
     pub fn length(&self) -> f32 {
         if let Some(length) = self.parameters.first() {
             *length
@@ -24,16 +27,26 @@ impl AddCylinder {
             default_diameter
         }
     }
+
+    /// The diameter at the end of the segment, which makes the segment a cone if it differs from
+    /// the diameter at its start.
+    pub fn end_diameter(&self, start_diameter: f32) -> f32 {
+        if let Some(diameter) = self.parameters.get(2) {
+            *diameter
+        } else {
+            start_diameter
+        }
+    }
 }
 
 // f(d)
 // Translation entlang der H-Achse um die Länge d ohne Konstruktion.
-#[derive(Debug, Deserialize)]
-pub struct MoveForward {
-    parameters: Vec<f32>,
+#[derive(Debug)]
+pub struct MoveForward<'a> {
+    parameters: Cow<'a, [f32]>,
 }
 
-impl MoveForward {
+impl<'a> MoveForward<'a> {
     pub fn length(&self) -> f32 {
         if let Some(length) = self.parameters.first() {
             *length
@@ -49,12 +62,12 @@ impl MoveForward {
 // Rotation um die r-Achse um den Winkel  (Pitch).
 // /(d)
 // Rotation um die h-Achse um den Winkel  (Roll).
-#[derive(Debug, Deserialize)]
-pub struct AngleCommand {
-    parameters: Vec<f32>,
+#[derive(Debug)]
+pub struct AngleCommand<'a> {
+    parameters: Cow<'a, [f32]>,
 }
 
-impl AngleCommand {
+impl<'a> AngleCommand<'a> {
     pub fn angle(&self) -> f32 {
         if let Some(&angle) = self.parameters.first() {
             angle
@@ -64,6 +77,70 @@ impl AngleCommand {
     }
 }
 
+// "(s), ;(s)
+// Skaliert die Länge bzw. den Durchmesser aller folgenden Segmente um den Faktor s.
+#[derive(Debug)]
+pub struct ScaleCommand<'a> {
+    parameters: Cow<'a, [f32]>,
+}
+
+impl<'a> ScaleCommand<'a> {
+    pub fn factor(&self) -> f32 {
+        if let Some(&factor) = self.parameters.first() {
+            factor
+        } else {
+            0.9
+        }
+    }
+}
+
+// @R(hx,hy,hz), @R(hx,hy,hz,ux,uy,uz)
+// Setzt die h-Achse (und optional die u-Achse) der Turtle auf die gegebenen Vektoren.
+#[derive(Debug)]
+pub struct SetHeading<'a> {
+    parameters: Cow<'a, [f32]>,
+}
+
+impl<'a> SetHeading<'a> {
+    pub fn heading(&self) -> Option<Vec3> {
+        self.parameters.get(0..3).map(Vec3::from_slice)
+    }
+
+    pub fn up(&self) -> Option<Vec3> {
+        self.parameters.get(3..6).map(Vec3::from_slice)
+    }
+}
+
+// @Ts(e)
+// Setzt die Stärke des Tropismus auf e bzw. ohne Parameter auf die Stärke der Instanz zurück.
+#[derive(Debug)]
+pub struct SetTropismStrength<'a> {
+    parameters: Cow<'a, [f32]>,
+}
+
+impl<'a> SetTropismStrength<'a> {
+    pub fn strength(&self) -> Option<f32> {
+        self.parameters.first().copied()
+    }
+}
+
+// @O(d), @c(d)
+// Zeichnet eine Kugel bzw. einen Kreis mit dem Durchmesser d an der Position der Turtle.
+#[derive(Debug)]
+pub struct AddShape<'a> {
+    parameters: Cow<'a, [f32]>,
+}
+
+impl<'a> AddShape<'a> {
+    pub fn diameter(&self, default_diameter: f32) -> f32 {
+        if let Some(&diameter) = self.parameters.first() {
+            diameter
+        } else {
+            default_diameter
+        }
+    }
+}
+
 // |
 // Drehung um 180° um die u-Achse (Shortcut für +(180))
 
@@ -73,7 +150,7 @@ impl AngleCommand {
 // ]
 // Der letzte Zustand wird vom Stack entfernt und die Turtle in diesen Zustand versetzt
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug)]
 pub struct SetDefaultCylinderDiameter {
     parameters: [f32; 1],
 }
@@ -87,116 +164,207 @@ impl SetDefaultCylinderDiameter {
     }
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(untagged)]
-pub enum PrimitiveCommandParameter {
-    String(String),
-    Usize(usize),
+#[derive(Debug)]
+pub struct PrimitiveCommand<'a> {
+    name: &'a str,
+    iteration: usize,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct PrimitiveCommand {
-    parameters: Vec<PrimitiveCommandParameter>,
-}
-
-impl PrimitiveCommand {
+impl<'a> PrimitiveCommand<'a> {
     pub fn name(&self) -> &str {
-        match self
-            .parameters
-            .get(0)
-            .expect("SurfaceCommand has no surface name")
-        {
-            PrimitiveCommandParameter::String(name) => name,
-            _ => panic!("SurfaceCommand's first parameter is not a String"),
-        }
+        self.name
     }
 
     pub fn iteration(&self) -> usize {
-        match self.parameters.get(1) {
-            None => 0,
-            Some(parameter) => match parameter {
-                PrimitiveCommandParameter::Usize(iteration) => *iteration,
-                _ => panic!("SurfaceCommand's second parameter is a String"),
-            },
-        }
+        self.iteration
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug)]
 pub struct SetMaterialIndex {
-    parameters: Vec<usize>,
+    material_index: Option<usize>,
 }
 
 impl SetMaterialIndex {
     pub fn material_index(&self) -> Option<&usize> {
-        self.parameters.first()
+        self.material_index.as_ref()
     }
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(tag = "name")]
-pub enum TurtleCommand {
-    #[serde(rename = "F")]
-    AddCylinder(AddCylinder),
-
-    #[serde(rename = "f")]
-    MoveForward(MoveForward),
-
-    #[serde(rename = "+")]
-    RotateYaw(AngleCommand),
-
-    #[serde(rename = "-")]
-    RotateYawNegative(AngleCommand),
-
-    #[serde(rename = "&")]
-    RotatePitch(AngleCommand),
-
-    #[serde(rename = "^")]
-    RotatePitchNegative(AngleCommand),
-
-    #[serde(rename = "/")]
-    RotateRoll(AngleCommand),
-
-    #[serde(rename = "\\")]
-    RotateRollNegative(AngleCommand),
-
-    #[serde(rename = "|")]
+/// A command for the turtle, which borrows its parameters from the module it was created from.
+#[derive(Debug)]
+pub enum TurtleCommand<'a> {
+    AddCylinder(AddCylinder<'a>),
+    MoveForward(MoveForward<'a>),
+    RotateYaw(AngleCommand<'a>),
+    RotateYawNegative(AngleCommand<'a>),
+    RotatePitch(AngleCommand<'a>),
+    RotatePitchNegative(AngleCommand<'a>),
+    RotateRoll(AngleCommand<'a>),
+    RotateRollNegative(AngleCommand<'a>),
     Yaw180,
-
-    #[serde(rename = "[")]
     PushToStack,
-
-    #[serde(rename = "]")]
     PopFromStack,
-
     // every command below this line is not needed for the exercise
-    #[serde(rename = "$")]
     ToUpPlane,
-
-    #[serde(rename = "!")]
     SetDefaultCylinderDiameter(SetDefaultCylinderDiameter),
-
-    #[serde(rename = "~")]
-    AddPredefinedPrimitive(PrimitiveCommand),
-
-    #[serde(rename = "{")]
+    AddPredefinedPrimitive(PrimitiveCommand<'a>),
     BeginPolygon,
-
-    #[serde(rename = "}")]
     EndPolygon,
+    MoveAlongEdge(MoveForward<'a>),
+    RecordVertex,
+    ScaleLength(ScaleCommand<'a>),
+    ScaleWidth(ScaleCommand<'a>),
+    SetHeading(SetHeading<'a>),
+    SetTropismStrength(SetTropismStrength<'a>),
+    AddSphere(AddShape<'a>),
+    AddCircle(AddShape<'a>),
+    SetMaterialIndex(SetMaterialIndex),
+    IgnoreRemainingBranch,
+    Query,
+    Unknown,
+}
 
-    #[serde(rename = "G")]
-    MoveAlongEdge(MoveForward),
+// scales the first parameter or, if there is none, the default value by the given factor
+fn scale_first_parameter(parameters: &mut Cow<[f32]>, default: f32, factor: f32) {
+    let parameters = parameters.to_mut();
+    if let Some(first) = parameters.first_mut() {
+        *first *= factor;
+    } else {
+        parameters.push(default * factor);
+    }
+}
 
-    #[serde(rename = ".")]
-    RecordVertex,
+// interpolates the first parameter or, if there is none, the default value between 1 and itself
+fn interpolate_first_parameter(parameters: &mut Cow<[f32]>, default: f32, t: f32) {
+    let parameters = parameters.to_mut();
+    if let Some(first) = parameters.first_mut() {
+        *first = 1. + (*first - 1.) * t;
+    } else {
+        parameters.push(1. + (default - 1.) * t);
+    }
+}
 
-    #[serde(rename = "´")]
-    SetMaterialIndex(SetMaterialIndex),
+impl<'a> TurtleCommand<'a> {
+    /// Scales the lengths, angles and scale factors of the command by the growth of the module it
+    /// was created from, e.g., to animate the development of timed L-systems.
+    pub fn grown(mut self, growth: f32) -> Self {
+        if growth < 1. {
+            match &mut self {
+                TurtleCommand::AddCylinder(AddCylinder { parameters })
+                | TurtleCommand::MoveForward(MoveForward { parameters })
+                | TurtleCommand::MoveAlongEdge(MoveForward { parameters }) => {
+                    scale_first_parameter(parameters, 1., growth)
+                }
+                TurtleCommand::RotateYaw(AngleCommand { parameters })
+                | TurtleCommand::RotateYawNegative(AngleCommand { parameters })
+                | TurtleCommand::RotatePitch(AngleCommand { parameters })
+                | TurtleCommand::RotatePitchNegative(AngleCommand { parameters })
+                | TurtleCommand::RotateRoll(AngleCommand { parameters })
+                | TurtleCommand::RotateRollNegative(AngleCommand { parameters }) => {
+                    scale_first_parameter(parameters, 90., growth)
+                }
+                TurtleCommand::ScaleLength(ScaleCommand { parameters })
+                | TurtleCommand::ScaleWidth(ScaleCommand { parameters }) => {
+                    interpolate_first_parameter(parameters, 0.9, growth)
+                }
+                _ => {}
+            }
+        }
+        self
+    }
+}
 
-    #[serde(rename = "%")]
-    IgnoreRemainingBranch,
+impl<'a> From<ModuleRef<'a>> for TurtleCommand<'a> {
+    fn from(module: ModuleRef<'a>) -> Self {
+        let parameters = || Cow::Borrowed(module.parameters());
+        match module.name() {
+            "F" => TurtleCommand::AddCylinder(AddCylinder {
+                parameters: parameters(),
+            }),
+            "f" => TurtleCommand::MoveForward(MoveForward {
+                parameters: parameters(),
+            }),
+            "+" => TurtleCommand::RotateYaw(AngleCommand {
+                parameters: parameters(),
+            }),
+            "-" => TurtleCommand::RotateYawNegative(AngleCommand {
+                parameters: parameters(),
+            }),
+            "&" => TurtleCommand::RotatePitch(AngleCommand {
+                parameters: parameters(),
+            }),
+            "^" => TurtleCommand::RotatePitchNegative(AngleCommand {
+                parameters: parameters(),
+            }),
+            "/" => TurtleCommand::RotateRoll(AngleCommand {
+                parameters: parameters(),
+            }),
+            "\\" => TurtleCommand::RotateRollNegative(AngleCommand {
+                parameters: parameters(),
+            }),
+            "|" => TurtleCommand::Yaw180,
+            "[" => TurtleCommand::PushToStack,
+            "]" => TurtleCommand::PopFromStack,
+            "$" => TurtleCommand::ToUpPlane,
+            "!" => match module.parameters().first() {
+                Some(&diameter) => {
+                    TurtleCommand::SetDefaultCylinderDiameter(SetDefaultCylinderDiameter {
+                        parameters: [diameter],
+                    })
+                }
+                None => TurtleCommand::Unknown,
+            },
+            "{" => TurtleCommand::BeginPolygon,
+            "}" => TurtleCommand::EndPolygon,
+            "G" => TurtleCommand::MoveAlongEdge(MoveForward {
+                parameters: parameters(),
+            }),
+            "." => TurtleCommand::RecordVertex,
+            "\"" => TurtleCommand::ScaleLength(ScaleCommand {
+                parameters: parameters(),
+            }),
+            ";" => TurtleCommand::ScaleWidth(ScaleCommand {
+                parameters: parameters(),
+            }),
+            "@R" => TurtleCommand::SetHeading(SetHeading {
+                parameters: parameters(),
+            }),
+            "@Ts" => TurtleCommand::SetTropismStrength(SetTropismStrength {
+                parameters: parameters(),
+            }),
+            "@O" => TurtleCommand::AddSphere(AddShape {
+                parameters: parameters(),
+            }),
+            "@c" => TurtleCommand::AddCircle(AddShape {
+                parameters: parameters(),
+            }),
+            "´" => TurtleCommand::SetMaterialIndex(SetMaterialIndex {
+                material_index: module.parameters().first().map(|&i| i.max(0.) as usize),
+            }),
+            "%" => TurtleCommand::IgnoreRemainingBranch,
+            "~" => match module.string_parameter(0) {
+                Some(name) => TurtleCommand::AddPredefinedPrimitive(PrimitiveCommand {
+                    name,
+                    iteration: primitive_iteration(&module.parameters()[1..]),
+                }),
+                None => TurtleCommand::Unknown,
+            },
+            // ~<primitive name> modules are shorthands for ~("<primitive name>", ...) modules
+            // (see DOCUMENTATION.md)
+            name if name.len() > 1 && name.starts_with('~') => {
+                TurtleCommand::AddPredefinedPrimitive(PrimitiveCommand {
+                    name: &name[1..],
+                    iteration: primitive_iteration(module.parameters()),
+                })
+            }
+            name if is_query_module(name) => TurtleCommand::Query,
+            _ => TurtleCommand::Unknown,
+        }
+    }
+}
 
-    #[serde(other)]
-    Unknown,
+// the iteration of a primitive defaults to 0
+fn primitive_iteration(parameters: &[f32]) -> usize {
+    parameters.first().map_or(0, |i| i.max(0.) as usize)
 }
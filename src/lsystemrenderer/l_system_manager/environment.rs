@@ -0,0 +1,221 @@
+use crate::lsystemrenderer::scene_descriptor::{EnvironmentDescriptor, VoxelShadowDescriptor};
+use glam::{IVec3, Mat4, Vec3};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// The state of a communication module `?E(...)` when the turtle interpreted it.
+#[derive(Clone, Debug)]
+pub struct EnvironmentQuery {
+    position: Vec3,
+    heading: Vec3,
+    parameters: Vec<f32>,
+}
+
+impl EnvironmentQuery {
+    pub fn new(position: Vec3, heading: Vec3, parameters: Vec<f32>) -> Self {
+        Self {
+            position,
+            heading,
+            parameters,
+        }
+    }
+    /// The turtle's position in the environment's space.
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+    /// The turtle's heading in the environment's space.
+    pub fn heading(&self) -> Vec3 {
+        self.heading
+    }
+    pub fn parameters(&self) -> &[f32] {
+        &self.parameters
+    }
+    /// The parameters of the communication module.
+    /// An environment may change their values but not their number.
+    pub fn parameters_mut(&mut self) -> &mut [f32] {
+        &mut self.parameters
+    }
+}
+
+/// An environment of open L-systems.
+/// After an L-system's module string has been interpreted, its environment receives the state of
+/// all communication modules `?E(...)` in the module string and writes back their parameters, e.g.,
+/// the light received at a position or a flag indicating a collision.
+///
+/// An environment may be shared by multiple L-systems, each of which is identified by a `source`.
+pub trait Environment: Send {
+    /// Answers the communication modules of the most recent module string of the given source.
+    fn respond(&mut self, source: usize, queries: &mut [EnvironmentQuery]);
+
+    /// Removes everything the given source has left in the environment.
+    fn remove(&mut self, _source: usize) {}
+}
+
+impl From<&EnvironmentDescriptor> for Arc<Mutex<dyn Environment>> {
+    fn from(descriptor: &EnvironmentDescriptor) -> Self {
+        match descriptor {
+            EnvironmentDescriptor::VoxelShadow(d) => Arc::new(Mutex::new(VoxelShadow::from(d))),
+        }
+    }
+}
+
+static NEXT_SOURCE: AtomicUsize = AtomicUsize::new(0);
+
+/// Connects an L-system to its environment.
+pub struct EnvironmentHandle {
+    environment: Arc<Mutex<dyn Environment>>,
+    source: usize,
+    transform: Mat4,
+}
+
+impl EnvironmentHandle {
+    /// Creates a new handle with a unique source.
+    /// The `transform` maps the turtle's space to the environment's space.
+    pub fn new(environment: Arc<Mutex<dyn Environment>>, transform: Mat4) -> Self {
+        Self {
+            environment,
+            source: NEXT_SOURCE.fetch_add(1, Ordering::Relaxed),
+            transform,
+        }
+    }
+
+    /// Creates a query for a communication module interpreted by a turtle with the given position
+    /// and heading in the turtle's space.
+    pub fn query(&self, position: Vec3, heading: Vec3, parameters: Vec<f32>) -> EnvironmentQuery {
+        EnvironmentQuery::new(
+            self.transform.transform_point3(position),
+            self.transform
+                .transform_vector3(heading)
+                .normalize_or_zero(),
+            parameters,
+        )
+    }
+
+    pub fn respond(&self, queries: &mut [EnvironmentQuery]) {
+        match self.environment.lock() {
+            Ok(mut environment) => environment.respond(self.source, queries),
+            Err(_) => log::error!("Environment is poisoned"),
+        }
+    }
+}
+
+impl Drop for EnvironmentHandle {
+    fn drop(&mut self) {
+        if let Ok(mut environment) = self.environment.lock() {
+            environment.remove(self.source);
+        }
+    }
+}
+
+/// A voxel-based approximation of the shadow cast by the communication modules of all sources, as
+/// described by Palubicki et al. in "Self-organizing tree models for image synthesis" (2009).
+///
+/// Each communication module casts a pyramid of shadow along the light's direction. The shadow a
+/// module casts onto a voxel `q` voxels away is `strength * decay^-q`.
+/// The first parameter of each communication module is set to the light it receives, i.e.,
+/// `max(0, 1 - shadow)`, where its own shadow is not taken into account.
+pub struct VoxelShadow {
+    voxel_size: f32,
+    depth: u32,
+    strength: f32,
+    decay: f32,
+    direction: Vec3,
+    shadow: HashMap<IVec3, f32>,
+    sources: HashMap<usize, Vec<Vec3>>,
+}
+
+impl VoxelShadow {
+    pub fn new(voxel_size: f32, depth: u32, strength: f32, decay: f32, direction: Vec3) -> Self {
+        Self {
+            voxel_size: voxel_size.max(f32::EPSILON),
+            depth,
+            strength,
+            decay,
+            direction: direction.try_normalize().unwrap_or(-Vec3::Y),
+            shadow: HashMap::new(),
+            sources: HashMap::new(),
+        }
+    }
+
+    fn voxel(&self, position: Vec3) -> IVec3 {
+        (position / self.voxel_size).floor().as_ivec3()
+    }
+
+    // adds (sign = 1) or removes (sign = -1) the shadow cast by a communication module
+    fn cast_shadow(&mut self, position: Vec3, sign: f32) {
+        // the pyramid spreads along the two axes the light's direction is least aligned with
+        let abs_direction = self.direction.abs();
+        let (u, v) = if abs_direction.x >= abs_direction.y && abs_direction.x >= abs_direction.z {
+            (IVec3::Y, IVec3::Z)
+        } else if abs_direction.y >= abs_direction.z {
+            (IVec3::X, IVec3::Z)
+        } else {
+            (IVec3::X, IVec3::Y)
+        };
+        for q in 0..=self.depth {
+            let center = self.voxel(position + self.direction * (q as f32 * self.voxel_size));
+            let shadow = sign * self.strength * self.decay.powi(-(q as i32));
+            let q = q as i32;
+            for i in -q..=q {
+                for j in -q..=q {
+                    let voxel = center + u * i + v * j;
+                    let value = self.shadow.entry(voxel).or_insert(0.);
+                    *value += shadow;
+                    if value.abs() < f32::EPSILON {
+                        self.shadow.remove(&voxel);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Default for VoxelShadow {
+    fn default() -> Self {
+        Self::new(1., 5, 0.5, 2., -Vec3::Y)
+    }
+}
+
+impl From<&VoxelShadowDescriptor> for VoxelShadow {
+    fn from(descriptor: &VoxelShadowDescriptor) -> Self {
+        let default = Self::default();
+        Self::new(
+            descriptor.voxel_size().unwrap_or(default.voxel_size),
+            descriptor.depth().unwrap_or(default.depth),
+            descriptor.strength().unwrap_or(default.strength),
+            descriptor.decay().unwrap_or(default.decay),
+            descriptor.direction().unwrap_or(default.direction),
+        )
+    }
+}
+
+impl Environment for VoxelShadow {
+    fn respond(&mut self, source: usize, queries: &mut [EnvironmentQuery]) {
+        self.remove(source);
+        let positions: Vec<Vec3> = queries.iter().map(|q| q.position()).collect();
+        for &position in positions.iter() {
+            self.cast_shadow(position, 1.);
+        }
+        self.sources.insert(source, positions);
+
+        for query in queries.iter_mut() {
+            let shadow = self
+                .shadow
+                .get(&self.voxel(query.position()))
+                .copied()
+                .unwrap_or(0.);
+            if let Some(light) = query.parameters_mut().first_mut() {
+                *light = (1. - (shadow - self.strength)).clamp(0., 1.);
+            }
+        }
+    }
+
+    fn remove(&mut self, source: usize) {
+        if let Some(positions) = self.sources.remove(&source) {
+            for position in positions {
+                self.cast_shadow(position, -1.);
+            }
+        }
+    }
+}
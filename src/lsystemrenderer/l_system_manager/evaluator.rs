@@ -0,0 +1,968 @@
+use crate::framework::scene::transform::Transform;
+use crate::lindenmayer::encoding::{Decoder, Encoder};
+use crate::lindenmayer::query::{
+    is_query_module, ENVIRONMENT_QUERY, HEADING_QUERY, POSITION_QUERY,
+};
+use crate::lindenmayer::random::Random;
+use crate::lindenmayer::timed::{ModuleHistory, TimedSnapshot};
+use crate::lindenmayer::{HomomorphicImage, LSystem, PartialImage};
+use crate::lsystemrenderer::instancing::Lineage;
+use crate::lsystemrenderer::l_system_manager::cache::IterationCache;
+use crate::lsystemrenderer::l_system_manager::command::TurtleCommand;
+use crate::lsystemrenderer::l_system_manager::environment::EnvironmentHandle;
+use crate::lsystemrenderer::l_system_manager::turtle::{
+    GrowthKeyframes, LSystemPrimitive, MaterialState, Tropism, TurtleInterpretation,
+    TurtleInterpreter,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+
+/// The maximum number of modules derived or turtle commands interpreted per evaluation step.
+const MODULES_PER_STEP: usize = 1 << 16;
+
+/// The time in milliseconds the worker waits for a cache entry before evaluating it itself.
+#[cfg(target_arch = "wasm32")]
+const CACHE_TIMEOUT: f32 = 1000.;
+
+/// Everything needed to interpret the module strings of an L-system instance.
+#[derive(Clone, Debug)]
+pub struct Interpreter {
+    transform: Transform,
+    material_state: MaterialState,
+    primitives: HashMap<String, LSystemPrimitive>,
+    tropism: Option<Tropism>,
+    material_seed: u64,
+    generalized_cylinders: bool,
+    joint_spheres: bool,
+}
+
+impl Interpreter {
+    pub fn new(
+        transform: Transform,
+        material_state: MaterialState,
+        primitives: HashMap<String, LSystemPrimitive>,
+        tropism: Option<Tropism>,
+        material_seed: u64,
+        generalized_cylinders: bool,
+        joint_spheres: bool,
+    ) -> Self {
+        Self {
+            transform,
+            material_state,
+            primitives,
+            tropism,
+            material_seed,
+            generalized_cylinders,
+            joint_spheres,
+        }
+    }
+
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    /// Describes the interpreter's settings, s.t. equal settings have equal descriptions.
+    pub fn fingerprint(&self) -> String {
+        let mut primitives: Vec<String> = self
+            .primitives
+            .iter()
+            .map(|(name, primitive)| format!("{}: {:?}", name, primitive))
+            .collect();
+        primitives.sort();
+        format!(
+            "{:?}\n{:?}\n{:?}\n{}\n{}\n{}\n{}",
+            self.transform,
+            self.material_state,
+            self.tropism,
+            self.material_seed,
+            self.generalized_cylinders,
+            self.joint_spheres,
+            primitives.join("\n")
+        )
+    }
+
+    /// Creates a turtle interpreter that starts from scratch.
+    pub fn turtle(&self) -> TurtleInterpreter {
+        TurtleInterpreter::new(
+            self.transform,
+            self.material_state.clone(),
+            self.primitives.clone(),
+            &self.tropism,
+            Random::new(self.material_seed),
+            self.generalized_cylinders,
+            self.joint_spheres,
+        )
+    }
+
+    /// Interprets the homomorphic image of a module string with the given history.
+    pub fn interpret(
+        &self,
+        image: &HomomorphicImage,
+        history: &[ModuleHistory],
+    ) -> TurtleInterpretation {
+        self.interpret_commands(
+            image.iter().map(|(module, origin)| {
+                (TurtleCommand::from(module), Lineage::from(&history[origin]))
+            }),
+        )
+    }
+
+    /// Interprets the given commands, each paired with the lineage of the module it was created
+    /// from.
+    pub fn interpret_commands<'a>(
+        &self,
+        commands: impl IntoIterator<Item = (TurtleCommand<'a>, Lineage)>,
+    ) -> TurtleInterpretation {
+        let mut turtle = self.turtle();
+        turtle.interpret(commands);
+        turtle.finish()
+    }
+}
+
+/// The progress of evaluating the iterations requested from an L-system instance.
+///
+/// Iterations are counted like in `LSystemManager::try_get_iteration`, i.e., iteration `0` is the
+/// first derived iteration.
+#[derive(Copy, Clone, Debug, Serialize)]
+pub struct EvaluationProgress {
+    iteration: u32,
+    #[serde(rename = "targetIteration")]
+    target_iteration: u32,
+    #[serde(rename = "modulesProcessed")]
+    modules_processed: usize,
+    #[serde(rename = "estimatedModulesRemaining")]
+    estimated_modules_remaining: usize,
+}
+
+impl EvaluationProgress {
+    /// The iteration that is evaluated next.
+    pub fn iteration(&self) -> u32 {
+        self.iteration
+    }
+    pub fn target_iteration(&self) -> u32 {
+        self.target_iteration
+    }
+    /// The number of modules derived and interpreted so far.
+    pub fn modules_processed(&self) -> usize {
+        self.modules_processed
+    }
+    /// The number of modules that still need to be derived and interpreted to reach the target
+    /// iteration, extrapolated from the growth of the last iteration.
+    pub fn estimated_modules_remaining(&self) -> usize {
+        self.estimated_modules_remaining
+    }
+}
+
+/// A derived and interpreted iteration.
+pub struct EvaluatedIteration {
+    interpretation: TurtleInterpretation,
+    snapshot: TimedSnapshot,
+}
+
+impl EvaluatedIteration {
+    pub fn into_parts(self) -> (TurtleInterpretation, TimedSnapshot) {
+        (self.interpretation, self.snapshot)
+    }
+}
+
+pub enum EvaluationMessage {
+    Progress(EvaluationProgress),
+    Evaluated(Box<EvaluatedIteration>),
+    /// An evicted iteration has been interpreted again.
+    Rebuilt(u32, Box<TurtleInterpretation>),
+    /// The keyframes of an iteration's growth have been interpreted.
+    Growth(u32, Box<GrowthKeyframes>),
+    /// An evicted snapshot of the given L-system iteration has been derived again, or `None` if
+    /// it can't be derived.
+    Snapshot(u32, Option<Box<TimedSnapshot>>),
+    Failed,
+    #[cfg(target_arch = "wasm32")]
+    Cache(CacheRequest),
+}
+
+/// A request to the page storing cache entries on the web.
+#[cfg(target_arch = "wasm32")]
+pub enum CacheRequest {
+    /// The worker waits for the entry with the given key (see `IterationWorker::receive_cache`).
+    Load(String),
+    /// The given entry should be stored under the given key.
+    Store(String, Vec<u8>),
+}
+
+/// The result of a single evaluation step.
+pub enum EvaluationStep {
+    /// The next iteration has been partially derived or interpreted.
+    Pending,
+    Evaluated(Box<EvaluatedIteration>),
+    Failed,
+}
+
+// an iteration that has been derived but not yet completely interpreted
+struct PendingInterpretation {
+    // the homomorphic image of the current module string, or `None` if it is the module string
+    // itself
+    image: Option<HomomorphicImage<'static>>,
+    turtle: TurtleInterpreter,
+}
+
+// the stage of the iteration that is being evaluated
+enum PendingIteration {
+    // the L-system is deriving the next iteration
+    Deriving,
+    // the homomorphism is applied to the derived iteration
+    Applying(PartialImage),
+    Interpreting(Box<PendingInterpretation>),
+}
+
+/// Derives and interprets the iterations of an L-system instance.
+///
+/// Iterations are evaluated in steps that each derive, apply the homomorphism to, or interpret a
+/// bounded number of modules, s.t. evaluating an iteration can be paused between steps.
+pub struct Evaluator {
+    // the L-system in its initial state, from which evicted snapshots are derived again
+    initial: LSystem,
+    l_system: LSystem,
+    interpreter: Interpreter,
+    environment: Option<EnvironmentHandle>,
+    pending: Option<PendingIteration>,
+    evaluated: u32,
+    modules_processed: usize,
+    previous_len: usize,
+    cache: Option<IterationCache>,
+}
+
+impl Evaluator {
+    pub fn new(
+        l_system: LSystem,
+        interpreter: Interpreter,
+        environment: Option<EnvironmentHandle>,
+    ) -> Self {
+        let mut evaluator = Self {
+            previous_len: l_system.current_modules().len(),
+            initial: l_system.clone(),
+            l_system,
+            interpreter,
+            environment,
+            pending: None,
+            evaluated: 0,
+            modules_processed: 0,
+            cache: None,
+        };
+
+        // query modules in the axiom are answered before the first derivation
+        if evaluator
+            .l_system
+            .current_modules()
+            .iter()
+            .any(|m| is_query_module(m.name()))
+        {
+            evaluator.interpret_current();
+        }
+        evaluator
+    }
+
+    pub fn snapshot(&self) -> TimedSnapshot {
+        self.l_system.snapshot()
+    }
+
+    /// Persists evaluated iterations in the given cache and restores them from it instead of
+    /// evaluating them again.
+    pub fn set_cache(&mut self, cache: IterationCache) {
+        self.cache = Some(cache);
+    }
+
+    pub fn cache(&self) -> Option<&IterationCache> {
+        self.cache.as_ref()
+    }
+
+    /// Returns true if the next iteration has been partially evaluated.
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// The L-system's iteration that is derived next, i.e., the iteration a cache entry must
+    /// contain to be restored.
+    pub fn next_iteration(&self) -> usize {
+        self.l_system.iteration() + 1
+    }
+
+    /// Encodes an evaluated iteration as a cache entry.
+    pub fn encode_iteration(&self, evaluated: &EvaluatedIteration) -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        evaluated.snapshot.encode(&mut encoder);
+        encoder.u64(self.l_system.next_id());
+        evaluated.interpretation.encode(&mut encoder);
+        encoder.into_bytes()
+    }
+
+    /// Restores the next iteration from a cache entry as if it had been evaluated.
+    /// Returns `None` if the entry is invalid or doesn't contain the next iteration.
+    pub fn restore(&mut self, data: &[u8]) -> Option<EvaluatedIteration> {
+        if self.pending.is_some() {
+            return None;
+        }
+        let mut decoder = Decoder::new(data);
+        let snapshot = TimedSnapshot::decode(
+            &mut decoder,
+            self.l_system.current_modules().symbols(),
+            self.l_system.current_modules().len(),
+        )?;
+        if snapshot.iteration() != self.next_iteration() {
+            return None;
+        }
+        let next_id = decoder.u64()?;
+        let interpretation = TurtleInterpretation::decode(&mut decoder)?;
+
+        self.previous_len = self.l_system.current_modules().len();
+        self.l_system.restore(snapshot.clone(), next_id);
+        self.modules_processed += snapshot.modules().len();
+        self.evaluated += 1;
+        Some(EvaluatedIteration {
+            interpretation,
+            snapshot,
+        })
+    }
+
+    // returns the homomorphic image of the L-system's current module string
+    // if the homomorphism can't be applied, the module string is interpreted as is
+    fn current_image(&self) -> HomomorphicImage<'_> {
+        self.l_system.homomorphic_image().unwrap_or_else(|error| {
+            log::error!("Could not apply homomorphism of L-system: {}", error);
+            HomomorphicImage::identity(self.l_system.current_modules())
+        })
+    }
+
+    /// Interprets the L-system's current module string and answers its queries.
+    pub fn interpret_current(&mut self) -> TurtleInterpretation {
+        let image = self.current_image();
+        let interpretation = self.interpreter.interpret(&image, self.l_system.history());
+        let origins = query_origins(&interpretation, &image);
+        self.answer_queries(&interpretation, &origins);
+        interpretation
+    }
+
+    // sets the parameters of query modules in the L-system's current module string to the state
+    // of the turtle when it interpreted them, communication modules are answered by the L-system's
+    // environment
+    // `origins` holds the index of the module in the module string each query was created from
+    fn answer_queries(&mut self, interpretation: &TurtleInterpretation, origins: &[usize]) {
+        let mut environment_indices = Vec::new();
+        let mut environment_queries = Vec::new();
+        for (query, &index) in interpretation.queries().iter().zip(origins) {
+            let module = self.l_system.current_modules().get(index).unwrap();
+            let values = match module.name() {
+                POSITION_QUERY => query.transform().position(),
+                HEADING_QUERY => query.transform().forward(),
+                ENVIRONMENT_QUERY => {
+                    if let Some(environment) = self.environment.as_ref() {
+                        environment_indices.push(index);
+                        environment_queries.push(environment.query(
+                            query.transform().position(),
+                            query.transform().forward(),
+                            module.parameters().to_vec(),
+                        ));
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            self.l_system
+                .set_query_parameters(index, &values.to_array());
+        }
+
+        if let Some(environment) = self.environment.as_ref() {
+            environment.respond(&mut environment_queries);
+            for (index, query) in environment_indices.into_iter().zip(environment_queries) {
+                self.l_system
+                    .set_query_parameters(index, query.parameters());
+            }
+        }
+    }
+
+    /// Derives, applies the homomorphism to, or interprets the next modules of the next
+    /// iteration.
+    pub fn step(&mut self) -> EvaluationStep {
+        let pending = match self.pending.take() {
+            Some(pending) => pending,
+            None => {
+                #[cfg(not(target_arch = "wasm32"))]
+                if let Some(evaluated) = self
+                    .cache
+                    .as_ref()
+                    .and_then(|cache| cache.load(self.next_iteration()))
+                    .and_then(|data| self.restore(&data))
+                {
+                    return EvaluationStep::Evaluated(Box::new(evaluated));
+                }
+                PendingIteration::Deriving
+            }
+        };
+        let mut pending = match pending {
+            PendingIteration::Deriving => {
+                let previous_len = self.l_system.current_modules().len();
+                self.pending = match self.l_system.continue_derivation(MODULES_PER_STEP) {
+                    Ok(false) => Some(PendingIteration::Deriving),
+                    Ok(true) => {
+                        self.previous_len = previous_len;
+                        Some(
+                            match self.l_system.start_homomorphic_image(
+                                self.l_system.current_modules(),
+                                self.l_system.iteration(),
+                            ) {
+                                Some(image) => PendingIteration::Applying(image),
+                                None => self.interpreting(None),
+                            },
+                        )
+                    }
+                    Err(error) => {
+                        log::error!("Could not derive next iteration of L-system: {}", error);
+                        return EvaluationStep::Failed;
+                    }
+                };
+                return EvaluationStep::Pending;
+            }
+            PendingIteration::Applying(mut image) => {
+                self.pending = match self.l_system.continue_homomorphic_image(
+                    &mut image,
+                    self.l_system.current_modules(),
+                    MODULES_PER_STEP,
+                ) {
+                    Ok(false) => Some(PendingIteration::Applying(image)),
+                    Ok(true) => Some(self.interpreting(Some(image.finish()))),
+                    // the module string is interpreted as is
+                    Err(error) => {
+                        log::error!("Could not apply homomorphism of L-system: {}", error);
+                        Some(self.interpreting(None))
+                    }
+                };
+                return EvaluationStep::Pending;
+            }
+            PendingIteration::Interpreting(pending) => pending,
+        };
+
+        let identity;
+        let image = match pending.image.as_ref() {
+            Some(image) => image,
+            None => {
+                identity = HomomorphicImage::identity(self.l_system.current_modules());
+                &identity
+            }
+        };
+        let start = pending.turtle.commands_interpreted();
+        let end = (start + MODULES_PER_STEP).min(image.len());
+        let history = self.l_system.history();
+        pending.turtle.interpret((start..end).map(|i| {
+            (
+                TurtleCommand::from(image.modules().get(i).unwrap()),
+                Lineage::from(&history[image.origin(i)]),
+            )
+        }));
+        if end < image.len() {
+            self.pending = Some(PendingIteration::Interpreting(pending));
+            return EvaluationStep::Pending;
+        }
+        let interpretation = pending.turtle.finish();
+        let origins = match pending.image.as_ref() {
+            Some(image) => query_origins(&interpretation, image),
+            None => query_origins(
+                &interpretation,
+                &HomomorphicImage::identity(self.l_system.current_modules()),
+            ),
+        };
+        self.answer_queries(&interpretation, &origins);
+        self.modules_processed += self.l_system.current_modules().len();
+        self.evaluated += 1;
+        let evaluated = EvaluatedIteration {
+            interpretation,
+            snapshot: self.l_system.snapshot(),
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(cache) = self.cache.as_ref() {
+            cache.store(
+                self.l_system.iteration(),
+                &self.encode_iteration(&evaluated),
+            );
+        }
+        EvaluationStep::Evaluated(Box::new(evaluated))
+    }
+
+    // starts interpreting the derived iteration with the given homomorphic image
+    fn interpreting(&self, image: Option<HomomorphicImage<'static>>) -> PendingIteration {
+        PendingIteration::Interpreting(Box::new(PendingInterpretation {
+            image,
+            turtle: self.interpreter.turtle(),
+        }))
+    }
+
+    /// Derives and interprets the next iteration.
+    /// Returns `None` if the derivation failed.
+    pub fn evaluate_next_iteration(&mut self) -> Option<EvaluatedIteration> {
+        loop {
+            match self.step() {
+                EvaluationStep::Pending => {}
+                EvaluationStep::Evaluated(evaluated) => return Some(*evaluated),
+                EvaluationStep::Failed => return None,
+            }
+        }
+    }
+
+    /// Returns an evaluator that derives the L-system from its initial state again, e.g., to
+    /// restore evicted snapshots, and restores iterations from the cache on native.
+    /// Returns `None` if the evaluator has an environment, whose responses to queries may have
+    /// changed since the iterations were evaluated.
+    pub fn rederivation(&self) -> Option<Evaluator> {
+        if self.environment.is_some() {
+            return None;
+        }
+        let mut evaluator = Evaluator::new(self.initial.clone(), self.interpreter.clone(), None);
+        if let Some(cache) = self.cache.clone() {
+            evaluator.set_cache(cache);
+        }
+        Some(evaluator)
+    }
+
+    /// Derives the snapshot of the given iteration of the L-system again (see `rederivation`).
+    pub fn rederive(&self, iteration: usize) -> Option<TimedSnapshot> {
+        let mut evaluator = self.rederivation()?;
+        while evaluator.l_system.iteration() < iteration {
+            evaluator.evaluate_next_iteration()?;
+        }
+        Some(evaluator.snapshot())
+    }
+
+    /// Interprets a snapshot of a previously evaluated iteration again.
+    pub fn rebuild(&self, snapshot: &TimedSnapshot) -> TurtleInterpretation {
+        let modules = snapshot.modules();
+        let image = self
+            .l_system
+            .homomorphic_image_of(modules, snapshot.iteration())
+            .unwrap_or_else(|_| HomomorphicImage::identity(modules));
+        self.interpreter.interpret(&image, snapshot.history())
+    }
+
+    /// Interprets a snapshot at the start and at the end of its growth from the given snapshot of
+    /// the previous iteration.
+    pub fn growth_keyframes(
+        &self,
+        previous: &TimedSnapshot,
+        snapshot: &TimedSnapshot,
+    ) -> GrowthKeyframes {
+        let terminal_ages = self.l_system.definition().terminal_ages();
+        let interpret = |fraction: f32| {
+            let modules = snapshot.interpolated_modules(previous.modules(), fraction);
+            let image = self
+                .l_system
+                .homomorphic_image_of(&modules, snapshot.iteration())
+                .unwrap_or_else(|_| HomomorphicImage::identity(&modules));
+            let history = snapshot.history();
+            self.interpreter
+                .interpret_commands(image.iter().map(|(module, origin)| {
+                    (
+                        TurtleCommand::from(module).grown(snapshot.growth(
+                            origin,
+                            fraction,
+                            terminal_ages,
+                        )),
+                        Lineage::from(&history[origin]),
+                    )
+                }))
+        };
+        GrowthKeyframes::new(interpret(0.), interpret(1.))
+    }
+
+    /// The number of iterations derived so far.
+    pub fn evaluated(&self) -> u32 {
+        self.evaluated
+    }
+
+    /// Returns the progress towards the given target iteration.
+    pub fn progress(&self, target_iteration: u32) -> EvaluationProgress {
+        let len = self.l_system.current_modules().len();
+        // the modules of a derived iteration count as processed once they are interpreted, an
+        // iteration that is being derived is estimated like the following ones
+        let (pending_len, interpreted) = match self.pending.as_ref() {
+            Some(PendingIteration::Interpreting(pending)) => (
+                len,
+                len * pending.turtle.commands_interpreted()
+                    / pending
+                        .image
+                        .as_ref()
+                        .map_or(len, |image| image.len())
+                        .max(1),
+            ),
+            Some(PendingIteration::Applying(_)) => (len, 0),
+            Some(PendingIteration::Deriving) | None => (0, 0),
+        };
+        let growth = len as f64 / self.previous_len.max(1) as f64;
+        let derived = matches!(
+            self.pending,
+            Some(PendingIteration::Applying(_) | PendingIteration::Interpreting(_))
+        );
+        let remaining_iterations =
+            (target_iteration + 1).saturating_sub(self.evaluated + derived as u32);
+        let mut estimated_modules_remaining = (pending_len - interpreted) as f64;
+        let mut next_len = len as f64;
+        for _ in 0..remaining_iterations {
+            next_len *= growth;
+            estimated_modules_remaining += next_len;
+        }
+        EvaluationProgress {
+            iteration: self.evaluated,
+            target_iteration,
+            modules_processed: self.modules_processed + interpreted,
+            estimated_modules_remaining: estimated_modules_remaining.min(usize::MAX as f64)
+                as usize,
+        }
+    }
+}
+
+// the index of the module in the original module string each query of an interpretation of the
+// given image was created from
+fn query_origins(interpretation: &TurtleInterpretation, image: &HomomorphicImage) -> Vec<usize> {
+    interpretation
+        .queries()
+        .iter()
+        .map(|query| image.origin(query.index()))
+        .collect()
+}
+
+/// Evaluates iterations of an L-system instance in the background.
+///
+/// On native, a worker thread owns the evaluator, s.t. deriving and interpreting large module
+/// strings doesn't block the render loop.
+/// On the web, there is no separate thread: the evaluator runs on the main thread and is polled
+/// once per frame (cooperative time-slicing). Each poll stops at a deadline, which is checked
+/// after each evaluation step, rebuilt iteration, derived snapshot, and growth animation.
+pub struct IterationWorker {
+    #[cfg(not(target_arch = "wasm32"))]
+    requests: Sender<WorkerRequest>,
+    #[cfg(not(target_arch = "wasm32"))]
+    messages: Receiver<EvaluationMessage>,
+    #[cfg(target_arch = "wasm32")]
+    evaluator: Evaluator,
+    #[cfg(target_arch = "wasm32")]
+    target_iteration: Option<u32>,
+    #[cfg(target_arch = "wasm32")]
+    rebuilds: Vec<(u32, TimedSnapshot)>,
+    #[cfg(target_arch = "wasm32")]
+    growth_requests: Vec<(u32, TimedSnapshot, TimedSnapshot)>,
+    #[cfg(target_arch = "wasm32")]
+    snapshot_requests: Vec<u32>,
+    // the iteration whose snapshot is being derived again and the evaluator deriving it
+    #[cfg(target_arch = "wasm32")]
+    rederivation: Option<(u32, Box<Evaluator>)>,
+    #[cfg(target_arch = "wasm32")]
+    messages: Vec<EvaluationMessage>,
+    // the key of the cache entry the worker waits for and when it stops waiting
+    #[cfg(target_arch = "wasm32")]
+    awaited_cache_entry: Option<(String, f32)>,
+    // the last iteration that has been looked up in the cache
+    #[cfg(target_arch = "wasm32")]
+    cache_lookup: usize,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+enum WorkerRequest {
+    TargetIteration(u32),
+    Rebuild(u32, Box<TimedSnapshot>),
+    Growth(u32, Box<(TimedSnapshot, TimedSnapshot)>),
+    Snapshot(u32),
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl IterationWorker {
+    pub fn new(mut evaluator: Evaluator) -> Self {
+        let (requests, requests_receiver) = channel::<WorkerRequest>();
+        let (messages_sender, messages) = channel();
+        let spawned = std::thread::Builder::new()
+            .name("l-system-evaluator".to_string())
+            .spawn(move || {
+                // the worker stops once its manager, and thus the sender of requests, is dropped
+                let mut target_iteration = None;
+                loop {
+                    // evaluation is paused while the iteration being evaluated is not requested
+                    let request = if target_iteration.is_some_and(|t| t >= evaluator.evaluated()) {
+                        match requests_receiver.try_recv() {
+                            Ok(target) => Some(target),
+                            Err(TryRecvError::Empty) => None,
+                            Err(TryRecvError::Disconnected) => return,
+                        }
+                    } else {
+                        match requests_receiver.recv() {
+                            Ok(target) => Some(target),
+                            Err(_) => return,
+                        }
+                    };
+                    match request {
+                        Some(WorkerRequest::TargetIteration(target)) => {
+                            target_iteration = Some(target);
+                            continue;
+                        }
+                        // rebuilding evicted iterations takes precedence over new iterations
+                        Some(WorkerRequest::Rebuild(iteration, snapshot)) => {
+                            let interpretation = evaluator.rebuild(&snapshot);
+                            if messages_sender
+                                .send(EvaluationMessage::Rebuilt(
+                                    iteration,
+                                    Box::new(interpretation),
+                                ))
+                                .is_err()
+                            {
+                                return;
+                            }
+                            continue;
+                        }
+                        Some(WorkerRequest::Snapshot(iteration)) => {
+                            let snapshot = evaluator.rederive(iteration as usize).map(Box::new);
+                            if messages_sender
+                                .send(EvaluationMessage::Snapshot(iteration, snapshot))
+                                .is_err()
+                            {
+                                return;
+                            }
+                            continue;
+                        }
+                        Some(WorkerRequest::Growth(iteration, snapshots)) => {
+                            let (previous, snapshot) = *snapshots;
+                            let keyframes = evaluator.growth_keyframes(&previous, &snapshot);
+                            if messages_sender
+                                .send(EvaluationMessage::Growth(iteration, Box::new(keyframes)))
+                                .is_err()
+                            {
+                                return;
+                            }
+                            continue;
+                        }
+                        None => {}
+                    }
+
+                    let message = match evaluator.step() {
+                        EvaluationStep::Pending => None,
+                        EvaluationStep::Evaluated(evaluated) => {
+                            Some(EvaluationMessage::Evaluated(evaluated))
+                        }
+                        EvaluationStep::Failed => {
+                            messages_sender.send(EvaluationMessage::Failed).ok();
+                            return;
+                        }
+                    };
+                    let progress = evaluator.progress(target_iteration.unwrap_or_default());
+                    let sent = message
+                        .map_or(Ok(()), |message| messages_sender.send(message))
+                        .and_then(|_| messages_sender.send(EvaluationMessage::Progress(progress)));
+                    if sent.is_err() {
+                        return;
+                    }
+                }
+            });
+        if let Err(error) = spawned {
+            log::error!("Could not spawn L-system evaluation thread: {}", error);
+        }
+        Self { requests, messages }
+    }
+
+    /// Requests all iterations up to the given iteration.
+    /// Evaluating iterations beyond it is paused until they are requested again.
+    pub fn request(&mut self, target_iteration: u32) {
+        self.requests
+            .send(WorkerRequest::TargetIteration(target_iteration))
+            .ok();
+    }
+
+    /// Requests the given iteration to be interpreted again from its snapshot.
+    pub fn rebuild(&mut self, iteration: u32, snapshot: TimedSnapshot) {
+        self.requests
+            .send(WorkerRequest::Rebuild(iteration, Box::new(snapshot)))
+            .ok();
+    }
+
+    /// Requests the keyframes of the given iteration's growth from its snapshot and the snapshot
+    /// of the previous iteration.
+    pub fn growth(&mut self, iteration: u32, previous: TimedSnapshot, snapshot: TimedSnapshot) {
+        self.requests
+            .send(WorkerRequest::Growth(
+                iteration,
+                Box::new((previous, snapshot)),
+            ))
+            .ok();
+    }
+
+    /// Requests the evicted snapshot of the given L-system iteration (see `Evaluator::rederive`).
+    pub fn snapshot(&mut self, iteration: u32) {
+        self.requests.send(WorkerRequest::Snapshot(iteration)).ok();
+    }
+
+    /// Returns the messages sent by the worker since the last call.
+    pub fn poll(&mut self, _deadline: f32) -> Vec<EvaluationMessage> {
+        self.messages.try_iter().collect()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl IterationWorker {
+    pub fn new(evaluator: Evaluator) -> Self {
+        Self {
+            evaluator,
+            target_iteration: None,
+            rebuilds: Vec::new(),
+            growth_requests: Vec::new(),
+            snapshot_requests: Vec::new(),
+            rederivation: None,
+            messages: Vec::new(),
+            awaited_cache_entry: None,
+            cache_lookup: 0,
+        }
+    }
+
+    /// Requests all iterations up to the given iteration.
+    /// Evaluating iterations beyond it is paused until they are requested again.
+    pub fn request(&mut self, target_iteration: u32) {
+        self.target_iteration = Some(target_iteration);
+    }
+
+    /// Requests the given iteration to be interpreted again from its snapshot.
+    pub fn rebuild(&mut self, iteration: u32, snapshot: TimedSnapshot) {
+        self.rebuilds.push((iteration, snapshot));
+    }
+
+    /// Requests the keyframes of the given iteration's growth from its snapshot and the snapshot
+    /// of the previous iteration.
+    pub fn growth(&mut self, iteration: u32, previous: TimedSnapshot, snapshot: TimedSnapshot) {
+        self.growth_requests.push((iteration, previous, snapshot));
+    }
+
+    /// Requests the evicted snapshot of the given L-system iteration (see `Evaluator::rederive`).
+    pub fn snapshot(&mut self, iteration: u32) {
+        self.snapshot_requests.push(iteration);
+    }
+
+    /// Receives the cache entry with the given key, or `None` if there is no such entry.
+    /// If the worker is waiting for the entry, it restores the iteration from it instead of
+    /// evaluating it.
+    pub fn receive_cache(&mut self, key: &str, data: Option<Vec<u8>>) {
+        if self
+            .awaited_cache_entry
+            .as_ref()
+            .is_none_or(|(awaited, _)| awaited != key)
+        {
+            return;
+        }
+        self.awaited_cache_entry = None;
+        if let Some(evaluated) = data.and_then(|data| self.evaluator.restore(&data)) {
+            self.messages
+                .push(EvaluationMessage::Evaluated(Box::new(evaluated)));
+        }
+    }
+
+    /// Evaluates requested iterations until either all of them have been evaluated or the given
+    /// deadline has passed, and returns the resulting messages.
+    pub fn poll(&mut self, deadline: f32) -> Vec<EvaluationMessage> {
+        let mut messages: Vec<EvaluationMessage> = self.messages.drain(..).collect();
+        // rebuilding evicted iterations takes precedence over new iterations, the remaining
+        // requests are handled in the next frame once the deadline has passed
+        while let Some((iteration, snapshot)) = self.rebuilds.pop() {
+            messages.push(EvaluationMessage::Rebuilt(
+                iteration,
+                Box::new(self.evaluator.rebuild(&snapshot)),
+            ));
+            if instant::now() as f32 >= deadline {
+                return messages;
+            }
+        }
+        // snapshots are derived again step by step, like new iterations
+        loop {
+            if self.rederivation.is_none() {
+                let iteration = match self.snapshot_requests.pop() {
+                    Some(iteration) => iteration,
+                    None => break,
+                };
+                match self.evaluator.rederivation() {
+                    Some(evaluator) => self.rederivation = Some((iteration, Box::new(evaluator))),
+                    None => {
+                        messages.push(EvaluationMessage::Snapshot(iteration, None));
+                        continue;
+                    }
+                }
+            }
+            let (iteration, evaluator) = self.rederivation.as_mut().unwrap();
+            let iteration = *iteration;
+            if !evaluator.is_pending() && evaluator.next_iteration() > iteration as usize {
+                let snapshot = evaluator.snapshot();
+                messages.push(EvaluationMessage::Snapshot(
+                    iteration,
+                    Some(Box::new(snapshot)),
+                ));
+                self.rederivation = None;
+                continue;
+            }
+            if let EvaluationStep::Failed = evaluator.step() {
+                messages.push(EvaluationMessage::Snapshot(iteration, None));
+                self.rederivation = None;
+            }
+            if instant::now() as f32 >= deadline {
+                return messages;
+            }
+        }
+        while let Some((iteration, previous, snapshot)) = self.growth_requests.pop() {
+            messages.push(EvaluationMessage::Growth(
+                iteration,
+                Box::new(self.evaluator.growth_keyframes(&previous, &snapshot)),
+            ));
+            if instant::now() as f32 >= deadline {
+                return messages;
+            }
+        }
+        let target_iteration = match self.target_iteration {
+            Some(target_iteration) => target_iteration,
+            None => return messages,
+        };
+        if let Some((_, timeout)) = self.awaited_cache_entry.as_ref() {
+            if (instant::now() as f32) < *timeout {
+                return messages;
+            }
+            self.awaited_cache_entry = None;
+        }
+        while target_iteration >= self.evaluator.evaluated() {
+            // each iteration is looked up in the cache once before it is derived
+            let next_iteration = self.evaluator.next_iteration();
+            if !self.evaluator.is_pending() && self.cache_lookup < next_iteration {
+                if let Some(cache) = self.evaluator.cache() {
+                    let key = cache.entry_key(next_iteration);
+                    self.cache_lookup = next_iteration;
+                    messages.push(EvaluationMessage::Cache(CacheRequest::Load(key.clone())));
+                    self.awaited_cache_entry = Some((key, instant::now() as f32 + CACHE_TIMEOUT));
+                    return messages;
+                }
+            }
+            match self.evaluator.step() {
+                EvaluationStep::Pending => {}
+                EvaluationStep::Evaluated(evaluated) => {
+                    if let Some(cache) = self.evaluator.cache() {
+                        messages.push(EvaluationMessage::Cache(CacheRequest::Store(
+                            cache.entry_key(evaluated.snapshot.iteration()),
+                            self.evaluator.encode_iteration(&evaluated),
+                        )));
+                    }
+                    messages.push(EvaluationMessage::Evaluated(evaluated))
+                }
+                EvaluationStep::Failed => {
+                    messages.push(EvaluationMessage::Failed);
+                    self.target_iteration = None;
+                    return messages;
+                }
+            }
+            if instant::now() as f32 >= deadline {
+                break;
+            }
+        }
+        messages.push(EvaluationMessage::Progress(
+            self.evaluator.progress(target_iteration),
+        ));
+        if target_iteration < self.evaluator.evaluated() {
+            self.target_iteration = None;
+        }
+        messages
+    }
+}
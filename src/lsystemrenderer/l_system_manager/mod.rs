@@ -1,101 +1,453 @@
 use crate::framework::context::Gpu;
 use crate::framework::event::lifecycle::Update;
+use crate::framework::geometry::bounds::Bounds3;
+use crate::framework::gpu::buffer::Buffer;
 use crate::framework::input::Input;
 use crate::framework::scene::transform::Transform;
-use crate::lsystemrenderer::l_system_manager::command::TurtleCommand;
+use crate::lindenmayer::random::Random;
+use crate::lindenmayer::timed::TimedSnapshot;
+use crate::lindenmayer::LSystem;
+use crate::lsystemrenderer::instancing::Instance;
+use crate::lsystemrenderer::l_system_manager::cache::IterationCache;
+use crate::lsystemrenderer::l_system_manager::environment::EnvironmentHandle;
+#[cfg(target_arch = "wasm32")]
+use crate::lsystemrenderer::l_system_manager::evaluator::CacheRequest;
+use crate::lsystemrenderer::l_system_manager::evaluator::{
+    EvaluationMessage, EvaluationProgress, Evaluator, Interpreter, IterationWorker,
+};
 use crate::lsystemrenderer::l_system_manager::turtle::{
-    LSystemModel, LSystemPrimitive, MaterialState, Tropism,
+    GrowthKeyframes, LSystemModel, LSystemPrimitive, MaterialState, Tropism, TurtleInterpretation,
 };
-use crate::LSystem;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use wgpu::BufferUsages;
 
+pub mod cache;
 pub mod command;
+pub mod environment;
+pub mod evaluator;
+pub mod surface;
 pub mod turtle;
 
+// an evaluated iteration whose model may have been evicted to save memory
+struct CachedIteration {
+    model: Option<LSystemModel>,
+    // the keyframes of the growth of the iteration's successor, if a growth animation uses them
+    growth: Option<GrowthKeyframes>,
+    last_used: f32,
+    rebuilding: bool,
+    growth_requested: bool,
+}
+
+impl CachedIteration {
+    fn new(model: LSystemModel, now: f32) -> Self {
+        Self {
+            model: Some(model),
+            growth: None,
+            last_used: now,
+            rebuilding: false,
+            growth_requested: false,
+        }
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.model.as_ref().map_or(0, |m| m.memory_usage())
+            + self.growth.as_ref().map_or(0, |g| g.memory_usage())
+    }
+}
+
 pub struct LSystemManager {
     gpu: Arc<Gpu>,
     max_time_to_iterate: f32,
-    transform: Transform,
+    // the L-system in its initial state, the evaluated iterations are owned by the worker
     l_system: LSystem,
-    max_target_iteration: u32,
-    iterations: Vec<LSystemModel>,
-    material_state: MaterialState,
-    primitives: HashMap<String, LSystemPrimitive>,
-    tropism: Option<Tropism>,
+    interpreter: Interpreter,
+    target_iteration: u32,
+    requested_iteration: u32,
+    derivation_failed: bool,
+    iterations: Vec<CachedIteration>,
+    // the snapshot of each derived L-system iteration, iteration `n` belongs to `iterations[n - 1]`
+    // and is evicted together with it
+    snapshots: Vec<Option<TimedSnapshot>>,
+    // snapshots can only be derived again if the L-system doesn't depend on an environment
+    snapshots_evictable: bool,
+    restoring_snapshots: HashSet<usize>,
+    worker: IterationWorker,
+    progress: Option<EvaluationProgress>,
+    #[cfg(target_arch = "wasm32")]
+    cache_requests: Vec<CacheRequest>,
 }
 
 impl LSystemManager {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        l_system: LSystem,
+        mut l_system: LSystem,
         transform: Transform,
         initial_material_state: Option<MaterialState>,
         primitives: HashMap<String, LSystemPrimitive>,
         tropism: Option<Tropism>,
+        seed: Option<u64>,
+        environment: Option<EnvironmentHandle>,
+        generalized_cylinders: bool,
+        joint_spheres: bool,
         gpu: &Arc<Gpu>,
     ) -> Self {
-        let mut iterations = Vec::new();
-        let commands: Vec<TurtleCommand> = serde_wasm_bindgen::from_value(l_system.next_raw())
-            .expect("Could not parse turtle commands");
+        // unseeded L-systems are different each time they are evaluated, and environments may
+        // respond differently, so only iterations of seeded L-systems without one are cached
+        let cached = seed.is_some() && environment.is_none();
+        let snapshots_evictable = environment.is_none();
+        let seed = seed.unwrap_or_else(|| Random::from_time().next_u64());
+        l_system.set_seed(seed);
+        // random materials use their own random number generator, s.t. each iteration starts with
+        // the same sequence of random materials
+        let material_seed = Random::new(!seed).next_u64();
+        let interpreter = Interpreter::new(
+            transform,
+            initial_material_state.unwrap_or_default(),
+            primitives,
+            tropism,
+            material_seed,
+            generalized_cylinders,
+            joint_spheres,
+        );
 
-        let material_state = initial_material_state.unwrap_or_default();
+        let mut evaluator = Evaluator::new(l_system.clone(), interpreter.clone(), environment);
+        if cached {
+            evaluator.set_cache(IterationCache::new(&l_system, &interpreter));
+        }
+        let mut snapshots = vec![Some(evaluator.snapshot())];
 
-        // evaluate the first iteration
-        iterations.push(LSystemModel::from_turtle_commands(
-            &commands,
-            transform,
-            material_state.clone(),
-            &primitives,
-            &tropism,
-            gpu,
-        ));
+        // evaluate the first iteration, if it can't be derived, the axiom is used instead
+        // all further iterations are evaluated in the background
+        let (interpretation, derivation_failed) = match evaluator.evaluate_next_iteration() {
+            Some(evaluated) => {
+                let (interpretation, snapshot) = evaluated.into_parts();
+                snapshots.push(Some(snapshot));
+                (interpretation, false)
+            }
+            None => (evaluator.interpret_current(), true),
+        };
 
         Self {
             gpu: gpu.clone(),
             max_time_to_iterate: 50.,
-            transform,
             l_system,
-            max_target_iteration: 0,
-            iterations,
-            material_state,
-            primitives,
-            tropism,
+            target_iteration: 0,
+            requested_iteration: 0,
+            derivation_failed,
+            iterations: vec![CachedIteration::new(
+                LSystemModel::from_interpretation(interpretation, transform, gpu),
+                0.,
+            )],
+            snapshots,
+            snapshots_evictable,
+            restoring_snapshots: HashSet::new(),
+            worker: IterationWorker::new(evaluator),
+            interpreter,
+            progress: None,
+            #[cfg(target_arch = "wasm32")]
+            cache_requests: Vec::new(),
+        }
+    }
+
+    fn create_model(&self, interpretation: TurtleInterpretation) -> LSystemModel {
+        LSystemModel::from_interpretation(interpretation, self.interpreter.transform(), &self.gpu)
+    }
+
+    pub fn l_system(&self) -> &LSystem {
+        &self.l_system
+    }
+
+    pub fn maybe_increase_target_iteration(&mut self, target_iteration: u32) {
+        self.target_iteration = target_iteration.max(self.target_iteration);
+    }
+
+    /// Sets the iteration up to which iterations are evaluated.
+    /// If it is lower than the iteration currently being evaluated, its evaluation is paused
+    /// until the iteration is requested again.
+    pub fn set_target_iteration(&mut self, target_iteration: u32) {
+        self.target_iteration = target_iteration;
+    }
+
+    /// Returns the progress of evaluating the requested iterations reported since the last call.
+    pub fn take_progress(&mut self) -> Option<EvaluationProgress> {
+        self.progress.take()
+    }
+
+    /// Returns the requests to the page storing cache entries made since the last call.
+    #[cfg(target_arch = "wasm32")]
+    pub fn take_cache_requests(&mut self) -> Vec<CacheRequest> {
+        self.cache_requests.drain(..).collect()
+    }
+
+    /// Receives the cache entry with the given key, or `None` if there is no such entry.
+    #[cfg(target_arch = "wasm32")]
+    pub fn receive_cache(&mut self, key: &str, data: Option<Vec<u8>>) {
+        self.worker.receive_cache(key, data);
+    }
+
+    /// Returns the iteration whose growth is shown at the given real-valued time, where time `n`
+    /// corresponds to iteration `n`, and the progress of its growth at that time.
+    /// Returns `None` if no iteration but the axiom has been evaluated yet.
+    /// Times beyond the last evaluated iteration are clamped.
+    pub fn growth_at(&self, time: f32) -> Option<(u32, f32)> {
+        let last_iteration = self.snapshots.len().checked_sub(1)?;
+        if last_iteration == 0 {
+            return None;
+        }
+        let time = time.clamp(0., last_iteration as f32);
+        let iteration = (time.ceil() as usize).clamp(1, last_iteration);
+        Some((iteration as u32, time - (iteration - 1) as f32))
+    }
+
+    // returns a copy of the snapshot of the given L-system iteration
+    // evicted snapshots are derived again in the background and returned once they are available
+    fn snapshot(&mut self, iteration: usize) -> Option<TimedSnapshot> {
+        match self.snapshots.get(iteration)? {
+            Some(snapshot) => Some(snapshot.clone()),
+            None => {
+                if self.restoring_snapshots.insert(iteration) {
+                    self.worker.snapshot(iteration as u32);
+                }
+                None
+            }
+        }
+    }
+
+    /// Marks the keyframes of the given iteration's growth (see `growth_at`) as used at the given
+    /// time.
+    /// If they are not available, they are interpreted in the background.
+    pub fn touch_growth(&mut self, iteration: u32, now: f32) {
+        let index = match (iteration as usize).checked_sub(1) {
+            Some(index) if index < self.iterations.len() => index,
+            _ => return,
+        };
+        let cached = &mut self.iterations[index];
+        cached.last_used = now;
+        if cached.growth.is_none() && !cached.growth_requested {
+            let previous = self.snapshot(index);
+            let snapshot = self.snapshot(index + 1);
+            if let (Some(previous), Some(snapshot)) = (previous, snapshot) {
+                self.iterations[index].growth_requested = true;
+                self.worker.growth(iteration, previous, snapshot);
+            }
+        }
+    }
+
+    fn growth_keyframes(&self, iteration: u32) -> Option<&GrowthKeyframes> {
+        self.iterations
+            .get((iteration as usize).checked_sub(1)?)?
+            .growth
+            .as_ref()
+    }
+
+    /// Creates a model of the given iteration at the given progress of its growth, scaled and
+    /// centered as if it had the given bounds.
+    /// Returns `None` if the keyframes of its growth are not available (see `touch_growth`).
+    pub fn growth_model(
+        &self,
+        iteration: u32,
+        progress: f32,
+        bounds: Bounds3,
+    ) -> Option<LSystemModel> {
+        let keyframes = self.growth_keyframes(iteration)?;
+        Some(LSystemModel::from_interpretation_in_bounds(
+            keyframes.interpolate(progress),
+            self.interpreter.transform(),
+            bounds,
+            &self.gpu,
+        ))
+    }
+
+    /// Updates a model created by `growth_model` for the given iteration and bounds to the given
+    /// progress of the iteration's growth by interpolating the transforms of its instances.
+    /// Returns false if the keyframes of the iteration's growth are not available.
+    pub fn update_growth_model(
+        &self,
+        model: &mut LSystemModel,
+        iteration: u32,
+        progress: f32,
+        bounds: Bounds3,
+    ) -> bool {
+        match self.growth_keyframes(iteration) {
+            Some(keyframes) => {
+                model.update_growth(keyframes, progress, self.interpreter.transform(), bounds);
+                true
+            }
+            None => false,
         }
     }
 
-    pub fn maybe_increase_max_iteration(&mut self, max_iteration: u32) {
-        self.max_target_iteration = max_iteration.max(self.max_target_iteration);
+    /// Blends the cylinders of two evaluated iterations at the given progress in `[0;1]` (see
+    /// `LSystemModel::blend_cylinders`).
+    /// Returns `None` if one of the iterations has not been evaluated yet.
+    pub fn blend_iterations(&self, from: u32, to: u32, progress: f32) -> Option<Buffer<Instance>> {
+        let from = self.iterations.get(from as usize)?.model.as_ref()?;
+        let to = self.iterations.get(to as usize)?.model.as_ref()?;
+        Some(Buffer::from_data(
+            "",
+            &LSystemModel::blend_cylinders(from, to, progress),
+            BufferUsages::STORAGE,
+            &self.gpu,
+        ))
     }
 
+    /// Returns the given iteration if its model is available, or else the closest iteration
+    /// below it (or above it, if there is none below) whose model is available.
     pub fn try_get_iteration(&self, iteration: u32) -> (u32, &LSystemModel) {
-        if self.iterations.len() as u32 > iteration {
-            (iteration, &self.iterations[iteration as usize])
+        let resident = |i: usize| self.iterations[i].model.as_ref().map(|m| (i as u32, m));
+        let last = (iteration as usize).min(self.iterations.len() - 1);
+        (0..=last)
+            .rev()
+            .find_map(resident)
+            .or_else(|| (last + 1..self.iterations.len()).find_map(resident))
+            .expect("L-system manager has no iteration models")
+    }
+
+    pub fn is_resident(&self, iteration: u32) -> bool {
+        self.iterations
+            .get(iteration as usize)
+            .is_some_and(|cached| cached.model.is_some())
+    }
+
+    /// Marks the given iteration as used at the given time.
+    /// If its model has been evicted, it is rebuilt in the background.
+    pub fn touch(&mut self, iteration: u32, now: f32) {
+        let cached = match self.iterations.get_mut(iteration as usize) {
+            Some(cached) => cached,
+            None => return,
+        };
+        cached.last_used = now;
+        if cached.model.is_none() && !cached.rebuilding {
+            if let Some(snapshot) = self.snapshot(iteration as usize + 1) {
+                self.iterations[iteration as usize].rebuilding = true;
+                self.worker.rebuild(iteration, snapshot);
+            }
+        }
+    }
+
+    /// The number of bytes occupied by the models, growth keyframes and snapshots of all evaluated
+    /// iterations.
+    pub fn memory_usage(&self) -> usize {
+        self.iterations
+            .iter()
+            .map(|c| c.memory_usage())
+            .sum::<usize>()
+            + self
+                .snapshots
+                .iter()
+                .flatten()
+                .map(|s| s.memory_usage())
+                .sum::<usize>()
+    }
+
+    // returns true if the snapshot of the given iteration can be evicted
+    fn is_snapshot_evictable(&self, iteration: usize) -> bool {
+        self.snapshots_evictable
+            && self
+                .snapshots
+                .get(iteration + 1)
+                .is_some_and(|s| s.is_some())
+    }
+
+    /// Returns the iteration whose model, growth keyframes or snapshot have been used least
+    /// recently and its last use, if it has not been used at the given time and holds anything
+    /// but the only available model.
+    pub fn least_recently_used(&self, now: f32) -> Option<(u32, f32)> {
+        let evictable_models = self.iterations.iter().filter(|c| c.model.is_some()).count() > 1;
+        self.iterations
+            .iter()
+            .enumerate()
+            .filter(|(i, c)| {
+                c.last_used < now
+                    && (c.growth.is_some()
+                        || evictable_models && c.model.is_some()
+                        || self.is_snapshot_evictable(*i))
+            })
+            .min_by(|(_, a), (_, b)| a.last_used.total_cmp(&b.last_used))
+            .map(|(i, c)| (i as u32, c.last_used))
+    }
+
+    /// Frees the model, the growth keyframes and the snapshot of the given iteration and returns
+    /// the number of bytes they occupied.
+    /// The only available model is kept, and so are snapshots that can't be derived again.
+    /// The model and keyframes are rebuilt from the iteration's snapshot, which is restored from
+    /// the cache or derived again, when they are used again.
+    pub fn evict(&mut self, iteration: u32) -> usize {
+        let snapshot = if self.is_snapshot_evictable(iteration as usize) {
+            self.snapshots[iteration as usize + 1]
+                .take()
+                .map_or(0, |s| s.memory_usage())
         } else {
-            let i = self.iterations.len() - 1;
-            (i as u32, &self.iterations[i])
+            0
+        };
+        let evictable_models = self.iterations.iter().filter(|c| c.model.is_some()).count() > 1;
+        let cached = match self.iterations.get_mut(iteration as usize) {
+            Some(cached) => cached,
+            None => return 0,
+        };
+        let memory_usage = cached.memory_usage();
+        cached.growth = None;
+        if evictable_models {
+            if let Some(model) = cached.model.take() {
+                model.destroy();
+            }
         }
+        memory_usage - cached.memory_usage() + snapshot
     }
 }
 
 impl Update for LSystemManager {
     fn update(&mut self, input: &Input) {
-        // if iterations have been requested but not yet evaluated, evaluate more iterations until
-        // either all requested iterations have been evaluated, or the time to iterate per frame is
-        // exceeded
-        while self.max_target_iteration >= self.iterations.len() as u32 {
-            let commands: Vec<TurtleCommand> =
-                serde_wasm_bindgen::from_value(self.l_system.next_raw())
-                    .expect("Could not parse turtle commands");
-            self.iterations.push(LSystemModel::from_turtle_commands(
-                &commands,
-                self.transform,
-                self.material_state.clone(),
-                &self.primitives,
-                &self.tropism,
-                &self.gpu,
-            ));
-            if instant::now() as f32 - input.time().now() >= self.max_time_to_iterate {
-                break;
+        // iterations that have been requested but not yet evaluated are evaluated in the
+        // background, on the web this takes at most the time to iterate per frame
+        if !self.derivation_failed && self.target_iteration != self.requested_iteration {
+            self.requested_iteration = self.target_iteration;
+            self.worker.request(self.target_iteration);
+        }
+        for message in self
+            .worker
+            .poll(input.time().now() + self.max_time_to_iterate)
+        {
+            match message {
+                EvaluationMessage::Progress(progress) => self.progress = Some(progress),
+                EvaluationMessage::Evaluated(evaluated) => {
+                    let (interpretation, snapshot) = evaluated.into_parts();
+                    let model = self.create_model(interpretation);
+                    self.snapshots.push(Some(snapshot));
+                    self.iterations
+                        .push(CachedIteration::new(model, input.time().now()));
+                }
+                EvaluationMessage::Rebuilt(iteration, interpretation) => {
+                    let model = self.create_model(*interpretation);
+                    if let Some(cached) = self.iterations.get_mut(iteration as usize) {
+                        cached.model = Some(model);
+                        cached.rebuilding = false;
+                    }
+                }
+                EvaluationMessage::Growth(iteration, keyframes) => {
+                    if let Some(cached) = (iteration as usize)
+                        .checked_sub(1)
+                        .and_then(|i| self.iterations.get_mut(i))
+                    {
+                        cached.growth = Some(*keyframes);
+                        cached.growth_requested = false;
+                    }
+                }
+                EvaluationMessage::Snapshot(iteration, Some(snapshot)) => {
+                    let iteration = iteration as usize;
+                    if let Some(evicted) = self.snapshots.get_mut(iteration) {
+                        *evicted = Some(*snapshot);
+                        self.restoring_snapshots.remove(&iteration);
+                    }
+                }
+                // snapshots that can't be derived again are not requested again
+                EvaluationMessage::Snapshot(iteration, None) => {
+                    log::error!("Could not derive evicted iteration {} again", iteration);
+                }
+                EvaluationMessage::Failed => self.derivation_failed = true,
+                #[cfg(target_arch = "wasm32")]
+                EvaluationMessage::Cache(request) => self.cache_requests.push(request),
             }
         }
     }
@@ -103,8 +455,8 @@ impl Update for LSystemManager {
 
 impl Drop for LSystemManager {
     fn drop(&mut self) {
-        for iteration in self.iterations.iter_mut() {
-            iteration.cylinder_instances_buffer().buffer().destroy();
+        for model in self.iterations.iter().filter_map(|c| c.model.as_ref()) {
+            model.destroy();
         }
     }
 }
@@ -0,0 +1,545 @@
+use crate::framework::context::Gpu;
+use crate::framework::gpu::buffer::Buffer;
+use crate::framework::mesh::vertex::{FromPositionNormal, Position, Vertex};
+use crate::framework::renderer::drawable::GpuMesh;
+use crate::lindenmayer::encoding::{Decoder, Encoder};
+use crate::lsystemrenderer::instancing::{Instance, Lineage, Material};
+use glam::{Mat4, Quat, Vec2, Vec3};
+use std::f32::consts::TAU;
+use std::mem::size_of;
+use std::ops::Range;
+use std::sync::Arc;
+use wgpu::BufferUsages;
+
+// the signed area of the triangle `abc`, positive if it is oriented counterclockwise
+fn signed_area(a: Vec2, b: Vec2, c: Vec2) -> f32 {
+    (b - a).perp_dot(c - a) * 0.5
+}
+
+fn is_in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    signed_area(a, b, p) >= 0. && signed_area(b, c, p) >= 0. && signed_area(c, a, p) >= 0.
+}
+
+/// Computes the normal of a possibly non-planar polygon with Newell's method.
+/// Returns `None` if the polygon is degenerate.
+fn polygon_normal(points: &[Vec3]) -> Option<Vec3> {
+    let normal = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .fold(Vec3::ZERO, |n, (a, b)| n + a.cross(*b));
+    (normal.length_squared() > f32::EPSILON).then(|| normal.normalize())
+}
+
+/// Triangulates a simple polygon by ear clipping in the plane orthogonal to the given normal,
+/// around which the polygon is oriented counterclockwise.
+/// If the polygon is not simple, its remaining vertices are triangulated as a fan.
+fn triangulate(points: &[Vec3], normal: Vec3) -> Vec<[usize; 3]> {
+    let u = normal.any_orthonormal_vector();
+    let v = normal.cross(u);
+    let projected: Vec<Vec2> = points
+        .iter()
+        .map(|p| Vec2::new(p.dot(u), p.dot(v)))
+        .collect();
+
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    let mut triangles = Vec::with_capacity(points.len().saturating_sub(2));
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let ear = (0..n).find(|&i| {
+            let (a, b, c) = (
+                remaining[(i + n - 1) % n],
+                remaining[i],
+                remaining[(i + 1) % n],
+            );
+            signed_area(projected[a], projected[b], projected[c]) > 0.
+                && !remaining.iter().any(|&p| {
+                    p != a
+                        && p != b
+                        && p != c
+                        && is_in_triangle(projected[p], projected[a], projected[b], projected[c])
+                })
+        });
+        match ear {
+            Some(i) => {
+                triangles.push([
+                    remaining[(i + n - 1) % n],
+                    remaining[i],
+                    remaining[(i + 1) % n],
+                ]);
+                remaining.remove(i);
+            }
+            None => break,
+        }
+    }
+    for i in 1..remaining.len().saturating_sub(1) {
+        triangles.push([remaining[0], remaining[i], remaining[i + 1]]);
+    }
+    triangles
+}
+
+/// The number of vertices around each ring of a generalized cylinder.
+const TUBE_SIDES: usize = 12;
+
+/// A cross-section of a generalized cylinder.
+#[derive(Copy, Clone, Debug)]
+pub struct TubeRing {
+    position: Vec3,
+    radius: f32,
+}
+
+impl TubeRing {
+    pub fn new(position: Vec3, radius: f32) -> Self {
+        Self { position, radius }
+    }
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+}
+
+/// The triangulated polygons and generalized cylinders of an L-system iteration in the turtle's
+/// space.
+///
+/// Polygons are two-sided, i.e., each triangle is stored once for each side.
+/// Consecutive surfaces with the same material and lineage share an instance, s.t. each instance
+/// holds the lineage of all of its faces.
+#[derive(Clone, Debug, Default)]
+pub struct SurfaceMesh {
+    vertices: Vec<Vertex>,
+    faces: Vec<[u32; 3]>,
+    instances: Vec<Instance>,
+    // the first face and the number of faces drawn with each instance
+    ranges: Vec<[u32; 2]>,
+}
+
+impl SurfaceMesh {
+    pub fn is_empty(&self) -> bool {
+        self.faces.is_empty()
+    }
+
+    /// The number of bytes occupied by the mesh on the CPU.
+    pub fn memory_usage(&self) -> usize {
+        self.vertices.len() * size_of::<Vertex>()
+            + self.faces.len() * size_of::<[u32; 3]>()
+            + self.instances.len() * size_of::<Instance>()
+            + self.ranges.len() * size_of::<[u32; 2]>()
+    }
+
+    /// Triangulates the polygon with the given vertices and adds it to the mesh.
+    /// Returns false if the polygon is degenerate, e.g., because it has less than three vertices.
+    pub fn add_polygon(&mut self, points: &[Vec3], material: Material, lineage: Lineage) -> bool {
+        let normal = match polygon_normal(points) {
+            Some(normal) if points.len() >= 3 => normal,
+            _ => return false,
+        };
+        let triangles = triangulate(points, normal);
+
+        let front = self.vertices.len() as u32;
+        let back = front + points.len() as u32;
+        self.vertices.extend(
+            points
+                .iter()
+                .map(|&p| Vertex::from_position_normal(p, normal)),
+        );
+        self.vertices.extend(
+            points
+                .iter()
+                .map(|&p| Vertex::from_position_normal(p, -normal)),
+        );
+        let first_face = self.faces.len() as u32;
+        for [a, b, c] in triangles {
+            let (a, b, c) = (a as u32, b as u32, c as u32);
+            self.faces.push([front + a, front + b, front + c]);
+            self.faces.push([back + a, back + c, back + b]);
+        }
+        self.finish_surface(first_face, material, lineage);
+        true
+    }
+
+    /// Sweeps a closed tube through the given rings and adds it to the mesh.
+    /// The rings are oriented by a parallel-transport frame, s.t. the tube doesn't twist.
+    /// Returns false if the tube is degenerate, e.g., because it has less than two distinct rings.
+    pub fn add_tube(&mut self, rings: &[TubeRing], material: Material, lineage: Lineage) -> bool {
+        let mut rings = rings.to_vec();
+        rings.dedup_by(|b, a| a.position.distance_squared(b.position) <= f32::EPSILON);
+        if rings.len() < 2 {
+            return false;
+        }
+        let directions: Vec<Vec3> = rings
+            .windows(2)
+            .map(|w| (w[1].position - w[0].position).normalize())
+            .collect();
+        // rings at joints are oriented along the bisector of their segments, unless the tube
+        // turns back on itself
+        let tangents: Vec<Vec3> = (0..rings.len())
+            .map(|i| {
+                let incoming = directions[i.saturating_sub(1)];
+                let outgoing = directions[i.min(directions.len() - 1)];
+                (incoming + outgoing).try_normalize().unwrap_or(incoming)
+            })
+            .collect();
+
+        let first_vertex = self.vertices.len() as u32;
+        let first_face = self.faces.len() as u32;
+        let mut normal = tangents[0].any_orthonormal_vector();
+        for (i, ring) in rings.iter().enumerate() {
+            if i > 0 {
+                normal = Quat::from_rotation_arc(tangents[i - 1], tangents[i]).mul_vec3(normal);
+                // prevent the frame from drifting due to rounding errors
+                normal = (normal - tangents[i] * normal.dot(tangents[i])).normalize();
+            }
+            let binormal = tangents[i].cross(normal);
+            for side in 0..TUBE_SIDES {
+                let angle = TAU * side as f32 / TUBE_SIDES as f32;
+                let radial = normal * angle.cos() + binormal * angle.sin();
+                self.vertices.push(Vertex::from_position_normal(
+                    ring.position + radial * ring.radius,
+                    radial,
+                ));
+            }
+        }
+        let sides = TUBE_SIDES as u32;
+        for i in 0..rings.len() as u32 - 1 {
+            let ring = first_vertex + i * sides;
+            let next_ring = ring + sides;
+            for side in 0..sides {
+                let next_side = (side + 1) % sides;
+                self.faces
+                    .push([ring + side, ring + next_side, next_ring + side]);
+                self.faces
+                    .push([ring + next_side, next_ring + next_side, next_ring + side]);
+            }
+        }
+
+        // both ends are closed by flat caps, the vertices of each ring are oriented
+        // counterclockwise around the tube's tangent
+        let last_ring = first_vertex + (rings.len() as u32 - 1) * sides;
+        let last = rings.len() - 1;
+        for (ring_vertex, position, normal, is_start) in [
+            (first_vertex, rings[0].position, -tangents[0], true),
+            (last_ring, rings[last].position, tangents[last], false),
+        ] {
+            let center = self.vertices.len() as u32;
+            self.vertices
+                .push(Vertex::from_position_normal(position, normal));
+            for side in 0..sides {
+                let position = self.vertices[(ring_vertex + side) as usize].position();
+                self.vertices
+                    .push(Vertex::from_position_normal(position, normal));
+            }
+            for side in 0..sides {
+                let (a, b) = (center + 1 + side, center + 1 + (side + 1) % sides);
+                self.faces.push(if is_start {
+                    [center, b, a]
+                } else {
+                    [center, a, b]
+                });
+            }
+        }
+        self.finish_surface(first_face, material, lineage);
+        true
+    }
+
+    // assigns all faces starting at the given face to an instance with the given material and
+    // lineage
+    fn finish_surface(&mut self, first_face: u32, material: Material, lineage: Lineage) {
+        let num_faces = self.faces.len() as u32 - first_face;
+        match self.instances.last() {
+            Some(instance) if instance.material() == material && instance.lineage() == lineage => {
+                self.ranges.last_mut().unwrap()[1] += num_faces;
+            }
+            _ => {
+                let mut instance = Instance::new(Mat4::IDENTITY, material);
+                instance.set_lineage(lineage);
+                self.instances.push(instance);
+                self.ranges.push([first_face, num_faces]);
+            }
+        }
+    }
+
+    pub fn encode(&self, encoder: &mut Encoder) {
+        encoder.slice(&self.vertices);
+        encoder.slice(&self.faces);
+        encoder.slice(&self.instances);
+        encoder.slice(&self.ranges);
+    }
+
+    pub fn decode(decoder: &mut Decoder) -> Option<Self> {
+        let mesh = Self {
+            vertices: decoder.slice()?,
+            faces: decoder.slice()?,
+            instances: decoder.slice()?,
+            ranges: decoder.slice()?,
+        };
+        let num_vertices = mesh.vertices.len() as u32;
+        let num_faces = mesh.faces.len() as u32;
+        let valid = mesh.instances.len() == mesh.ranges.len()
+            && mesh.faces.iter().flatten().all(|&i| i < num_vertices)
+            && mesh
+                .ranges
+                .iter()
+                .all(|&[first, len]| first.checked_add(len).is_some_and(|end| end <= num_faces));
+        valid.then_some(mesh)
+    }
+}
+
+/// The polygons and generalized cylinders of an L-system model on the GPU.
+pub struct GpuSurfaces {
+    mesh: Arc<GpuMesh>,
+    instances: Buffer<Instance>,
+    // the indices drawn with each instance
+    index_ranges: Vec<Range<u32>>,
+}
+
+impl GpuSurfaces {
+    /// Uploads a surface mesh to the GPU, transformed by the given model transform.
+    /// Returns `None` if the mesh is empty.
+    pub fn new(mesh: SurfaceMesh, model_transform: Mat4, gpu: &Arc<Gpu>) -> Option<Self> {
+        if mesh.is_empty() {
+            return None;
+        }
+        let SurfaceMesh {
+            vertices,
+            faces,
+            mut instances,
+            ranges,
+        } = mesh;
+        instances.iter_mut().for_each(|i| {
+            i.set_matrix(model_transform.mul_mat4(&i.matrix()));
+        });
+        Some(Self {
+            mesh: Arc::new(GpuMesh::new(
+                &"Surfaces".to_string(),
+                &faces,
+                &vertices,
+                gpu.device(),
+            )),
+            instances: Buffer::from_data("", &instances, BufferUsages::STORAGE, gpu),
+            index_ranges: ranges
+                .iter()
+                .map(|&[first, len]| first * 3..(first + len) * 3)
+                .collect(),
+        })
+    }
+
+    pub fn mesh(&self) -> &Arc<GpuMesh> {
+        &self.mesh
+    }
+    pub fn instances(&self) -> &Buffer<Instance> {
+        &self.instances
+    }
+    /// The range of indices in the mesh's index buffer drawn with each instance.
+    pub fn index_ranges(&self) -> &Vec<Range<u32>> {
+        &self.index_ranges
+    }
+
+    /// The number of bytes occupied by the surfaces on the GPU.
+    pub fn memory_usage(&self) -> usize {
+        self.mesh.vertex_count() as usize * size_of::<Vertex>()
+            + self.mesh.index_count() as usize * size_of::<u32>()
+            + self.instances.size() as usize
+    }
+
+    pub fn destroy(&self) {
+        self.mesh.vertex_buffer().destroy();
+        self.mesh.index_buffer().destroy();
+        self.instances.buffer().destroy();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::mesh::vertex::Normal;
+
+    // the area of each triangle of a triangulation, signed by its orientation around the normal
+    fn triangle_areas(points: &[Vec3], normal: Vec3) -> Vec<f32> {
+        triangulate(points, normal)
+            .iter()
+            .map(|&[a, b, c]| {
+                (points[b] - points[a])
+                    .cross(points[c] - points[a])
+                    .dot(normal)
+                    * 0.5
+            })
+            .collect()
+    }
+
+    fn points(xy: &[[f32; 2]]) -> Vec<Vec3> {
+        xy.iter().map(|&[x, y]| Vec3::new(x, y, 0.)).collect()
+    }
+
+    #[test]
+    fn triangulates_convex_polygons() {
+        let quad = points(&[[0., 0.], [2., 0.], [2., 1.], [0., 1.]]);
+        let normal = polygon_normal(&quad).unwrap();
+        assert!(normal.abs_diff_eq(Vec3::Z, 1e-6));
+        let areas = triangle_areas(&quad, normal);
+        assert_eq!(areas.len(), 2);
+        assert!(areas.iter().all(|&a| a > 0.));
+        assert!((areas.iter().sum::<f32>() - 2.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn triangulates_concave_polygons() {
+        let l_shape = points(&[[2., 0.], [2., 1.], [1., 1.], [1., 2.], [0., 2.], [0., 0.]]);
+        let normal = polygon_normal(&l_shape).unwrap();
+        assert!(normal.abs_diff_eq(Vec3::Z, 1e-6));
+        // a fan around the first vertex would contain a clockwise triangle, because the vertex at
+        // (1, 1) is reflex
+        let areas = triangle_areas(&l_shape, normal);
+        assert_eq!(areas.len(), 4);
+        assert!(areas.iter().all(|&a| a > 0.));
+        assert!((areas.iter().sum::<f32>() - 3.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn triangulates_clockwise_polygons_around_the_opposite_normal() {
+        let mut l_shape = points(&[[2., 0.], [2., 1.], [1., 1.], [1., 2.], [0., 2.], [0., 0.]]);
+        l_shape.reverse();
+        let normal = polygon_normal(&l_shape).unwrap();
+        assert!(normal.abs_diff_eq(-Vec3::Z, 1e-6));
+        let areas = triangle_areas(&l_shape, normal);
+        assert_eq!(areas.len(), 4);
+        assert!(areas.iter().all(|&a| a > 0.));
+        assert!((areas.iter().sum::<f32>() - 3.).abs() < 1e-6);
+
+        let mut mesh = SurfaceMesh::default();
+        assert!(mesh.add_polygon(&l_shape, Material::default(), Lineage::UNKNOWN));
+        // both sides face outwards
+        for &[a, b, c] in mesh.faces.iter() {
+            let [a, b, c] = [a, b, c].map(|i| mesh.vertices[i as usize]);
+            let face_normal = (b.position() - a.position()).cross(c.position() - a.position());
+            assert!(face_normal.dot(a.normal()) > 0.);
+        }
+    }
+
+    #[test]
+    fn rejects_degenerate_polygons() {
+        let collinear = points(&[[0., 0.], [1., 1.], [2., 2.], [3., 3.]]);
+        assert_eq!(polygon_normal(&collinear), None);
+        let mut mesh = SurfaceMesh::default();
+        assert!(!mesh.add_polygon(&collinear, Material::default(), Lineage::UNKNOWN));
+        assert!(!mesh.add_polygon(&collinear[..2], Material::default(), Lineage::UNKNOWN));
+        assert!(mesh.is_empty());
+        assert!(mesh.instances.is_empty());
+    }
+
+    // checks the vertices and faces of a tube through the given points
+    fn check_tube(points: &[Vec3]) {
+        let rings: Vec<TubeRing> = points.iter().map(|&p| TubeRing::new(p, 0.1)).collect();
+        let mut mesh = SurfaceMesh::default();
+        assert!(mesh.add_tube(&rings, Material::default(), Lineage::UNKNOWN));
+
+        // each ring and each cap consists of a vertex per side, caps have an additional center
+        let sides = TUBE_SIDES;
+        let num_rings = points.len();
+        assert_eq!(mesh.vertices.len(), num_rings * sides + 2 * (sides + 1));
+        // two triangles per side between consecutive rings, and one per side for each cap
+        assert_eq!(mesh.faces.len(), 2 * sides * (num_rings - 1) + 2 * sides);
+        assert_eq!(mesh.ranges, vec![[0, mesh.faces.len() as u32]]);
+
+        let start = (points[1] - points[0]).normalize();
+        let end = (points[num_rings - 1] - points[num_rings - 2]).normalize();
+        for (i, ring) in mesh.vertices[..num_rings * sides].chunks(sides).enumerate() {
+            for vertex in ring {
+                assert!((vertex.position().distance(points[i]) - 0.1).abs() < 1e-5);
+                assert!(vertex
+                    .normal()
+                    .abs_diff_eq((vertex.position() - points[i]) / 0.1, 1e-4));
+            }
+        }
+        // the frame doesn't twist, i.e., the first vertex of each ring stays on the same side
+        for i in 1..num_rings {
+            let previous = mesh.vertices[(i - 1) * sides].normal();
+            assert!(mesh.vertices[i * sides].normal().dot(previous) > 0.5);
+        }
+        let caps = &mesh.vertices[num_rings * sides..];
+        assert!(caps[..=sides]
+            .iter()
+            .all(|v| v.normal().abs_diff_eq(-start, 1e-6)));
+        assert!(caps[sides + 1..]
+            .iter()
+            .all(|v| v.normal().abs_diff_eq(end, 1e-6)));
+        // all faces, including the caps, are oriented counterclockwise seen from outside
+        for &[a, b, c] in mesh.faces.iter() {
+            let [a, b, c] = [a, b, c].map(|i| mesh.vertices[i as usize]);
+            let face_normal = (b.position() - a.position()).cross(c.position() - a.position());
+            let normal = a.normal() + b.normal() + c.normal();
+            assert!(face_normal.dot(normal) > 0.);
+        }
+    }
+
+    #[test]
+    fn sweeps_straight_tubes() {
+        check_tube(&[Vec3::ZERO, Vec3::Y, Vec3::Y * 2.]);
+    }
+
+    #[test]
+    fn sweeps_bent_tubes() {
+        check_tube(&[
+            Vec3::ZERO,
+            Vec3::Y,
+            Vec3::new(1., 1., 0.),
+            Vec3::new(1., 1., 1.),
+        ]);
+    }
+
+    #[test]
+    fn rejects_degenerate_tubes() {
+        let mut mesh = SurfaceMesh::default();
+        let rings = [TubeRing::new(Vec3::ONE, 0.1), TubeRing::new(Vec3::ONE, 0.2)];
+        assert!(!mesh.add_tube(&rings, Material::default(), Lineage::UNKNOWN));
+        assert!(mesh.is_empty());
+    }
+
+    fn encode(mesh: &SurfaceMesh) -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        mesh.encode(&mut encoder);
+        encoder.into_bytes()
+    }
+
+    fn mesh() -> SurfaceMesh {
+        let mut mesh = SurfaceMesh::default();
+        let quad = points(&[[0., 0.], [2., 0.], [2., 1.], [0., 1.]]);
+        mesh.add_polygon(&quad, Material::default(), Lineage::UNKNOWN);
+        mesh.add_polygon(
+            &quad,
+            Material::new(Vec3::X, Vec3::ONE, 1.),
+            Lineage::UNKNOWN,
+        );
+        mesh
+    }
+
+    #[test]
+    fn decodes_encoded_meshes() {
+        let bytes = encode(&mesh());
+        let decoded = SurfaceMesh::decode(&mut Decoder::new(&bytes)).unwrap();
+        assert_eq!(decoded.instances.len(), 2);
+        assert_eq!(decoded.ranges, vec![[0, 4], [4, 4]]);
+        assert_eq!(encode(&decoded), bytes);
+    }
+
+    #[test]
+    fn rejects_truncated_and_corrupted_meshes() {
+        let bytes = encode(&mesh());
+        for len in 0..bytes.len() {
+            assert!(SurfaceMesh::decode(&mut Decoder::new(&bytes[..len])).is_none());
+        }
+        let decode = |mesh: SurfaceMesh| SurfaceMesh::decode(&mut Decoder::new(&encode(&mesh)));
+        // faces referring to missing vertices
+        let mut corrupted = mesh();
+        corrupted.vertices.pop();
+        assert!(decode(corrupted).is_none());
+        // ranges beyond the faces
+        let mut corrupted = mesh();
+        corrupted.ranges[1] = [4, u32::MAX];
+        assert!(decode(corrupted).is_none());
+        // an instance without a range
+        let mut corrupted = mesh();
+        corrupted.ranges.pop();
+        assert!(decode(corrupted).is_none());
+    }
+}
@@ -2,12 +2,16 @@ use crate::framework::context::Gpu;
 use crate::framework::geometry::bounds::{Bounds, Bounds3};
 use crate::framework::gpu::buffer::Buffer;
 use crate::framework::scene::transform::{OrthonormalBasis, Transform, Transformable};
-use crate::lsystemrenderer::instancing::{Instance, Material};
+use crate::lindenmayer::encoding::{Decoder, Encoder};
+use crate::lindenmayer::random::Random;
+use crate::lsystemrenderer::instancing::{Instance, Lineage, Material};
 use crate::lsystemrenderer::l_system_manager::command::TurtleCommand;
+use crate::lsystemrenderer::l_system_manager::surface::{GpuSurfaces, SurfaceMesh, TubeRing};
 use crate::lsystemrenderer::scene_descriptor::LSystemInstance;
 use glam::{Mat4, Quat, Vec3};
 use serde::Deserialize;
 use std::collections::{HashMap, VecDeque};
+use std::mem::size_of;
 use std::sync::Arc;
 use wgpu::BufferUsages;
 
@@ -25,6 +29,15 @@ impl Tropism {
     }
 }
 
+/// The number of distinct tapers cones are drawn with, i.e., the ratio of a cone's narrow and wide
+/// radius is rounded to a multiple of `1 / CONE_TAPERS`.
+pub const CONE_TAPERS: u32 = 64;
+
+/// The ratio of the narrow and the wide radius of cones with the given taper.
+pub fn cone_taper(taper: u32) -> f32 {
+    taper as f32 / CONE_TAPERS as f32
+}
+
 #[derive(Clone, Debug)]
 pub struct LSystemPrimitive {
     aabb: Bounds3,
@@ -61,18 +74,19 @@ enum MaterialMode {
 #[derive(Clone, Debug, Default)]
 pub struct MaterialState {
     material_mode: MaterialMode,
-    materials: Vec<Material>,
+    // shared, s.t. pushing the turtle's state onto its stack doesn't copy them
+    materials: Arc<[Material]>,
 }
 
 impl From<&LSystemInstance> for MaterialState {
     fn from(instance: &LSystemInstance) -> Self {
         let (materials, material_mode) = if let Some(materials) = instance.materials() {
             (
-                materials.clone(),
+                materials.as_slice().into(),
                 MaterialMode::MaterialIndex(instance.start_material()),
             )
         } else {
-            (Vec::new(), MaterialMode::default())
+            (Arc::default(), MaterialMode::default())
         };
         Self {
             materials,
@@ -88,6 +102,13 @@ struct TurtleState {
     material_state: MaterialState,
     default_cylinder_diameter: f32,
     ignoring_branch_depth: u32,
+    // the generalized cylinder the turtle extends when drawing line segments
+    tube: Option<usize>,
+    // factors applied to the lengths and diameters of all following line segments
+    length_scale: f32,
+    width_scale: f32,
+    // overrides the strength of the L-system's tropism
+    tropism_strength: Option<f32>,
 }
 
 impl TurtleState {
@@ -106,24 +127,28 @@ impl TurtleState {
         self.transform
     }
 
-    #[cfg(target_arch = "wasm32")]
-    fn make_random_material(&self) -> Material {
-        let color = Vec3::new(
-            js_sys::Math::random() as f32,
-            js_sys::Math::random() as f32,
-            js_sys::Math::random() as f32,
-        );
-        Material::new(color, color, js_sys::Math::random() as f32 * 128.0)
+    fn make_random_material(random: &mut Random) -> Material {
+        let color = Vec3::new(random.next_f32(), random.next_f32(), random.next_f32());
+        Material::new(color, color, random.next_f32() * 128.0)
     }
 
-    pub fn get_material(&self) -> Material {
+    pub fn get_material(&self, random: &mut Random) -> Material {
         match self.material_state.material_mode {
-            MaterialMode::MaterialIndex(idx) => *self
+            MaterialMode::MaterialIndex(idx) => self
                 .material_state
                 .materials
                 .get(idx)
-                .unwrap_or(&self.make_random_material()),
-            MaterialMode::Random => self.make_random_material(),
+                .copied()
+                .unwrap_or_else(|| Self::make_random_material(random)),
+            MaterialMode::Random => Self::make_random_material(random),
+        }
+    }
+
+    // the material used for the next primitive, unless it is random
+    fn fixed_material(&self) -> Option<Material> {
+        match self.material_state.material_mode {
+            MaterialMode::MaterialIndex(idx) => self.material_state.materials.get(idx).copied(),
+            MaterialMode::Random => None,
         }
     }
 
@@ -140,36 +165,347 @@ impl Default for TurtleState {
             material_state: Default::default(),
             default_cylinder_diameter: 0.5,
             ignoring_branch_depth: 0,
+            tube: None,
+            length_scale: 1.,
+            width_scale: 1.,
+            tropism_strength: None,
         }
     }
 }
 
-pub struct LSystemModel {
+/// The state of the turtle when it interpreted a query command.
+#[derive(Copy, Clone, Debug)]
+pub struct TurtleQuery {
+    index: usize,
+    transform: Transform,
+}
+
+impl TurtleQuery {
+    /// The index of the query command in the interpreted list of commands.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+    /// The turtle's transform in the turtle's space, i.e., before the L-system's transform is
+    /// applied.
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+}
+
+/// The result of interpreting a list of turtle commands on the CPU.
+#[derive(Clone)]
+pub struct TurtleInterpretation {
     aabb: Bounds3,
-    cylinder_instances_buffer: Buffer<Instance>,
-    primitive_instances_buffers: HashMap<String, HashMap<usize, Buffer<Instance>>>,
+    cylinder_instances: Vec<Instance>,
+    cone_instances: HashMap<u32, Vec<Instance>>,
+    sphere_instances: Vec<Instance>,
+    circle_instances: Vec<Instance>,
+    primitive_instances: HashMap<String, HashMap<usize, Vec<Instance>>>,
+    surfaces: SurfaceMesh,
+    queries: Vec<TurtleQuery>,
 }
 
-impl LSystemModel {
-    pub fn from_turtle_commands(
-        commands: &Vec<TurtleCommand>,
+impl TurtleInterpretation {
+    pub fn queries(&self) -> &Vec<TurtleQuery> {
+        &self.queries
+    }
+
+    // all lists of instances, each of which is drawn from its own buffer in an `LSystemModel`
+    fn instance_lists(&self) -> Vec<(InstanceList, &Vec<Instance>)> {
+        let mut lists = vec![
+            (InstanceList::Cylinders, &self.cylinder_instances),
+            (InstanceList::Spheres, &self.sphere_instances),
+            (InstanceList::Circles, &self.circle_instances),
+        ];
+        for (&taper, instances) in self.cone_instances.iter() {
+            lists.push((InstanceList::Cones(taper), instances));
+        }
+        for (name, primitive) in self.primitive_instances.iter() {
+            for (&material, instances) in primitive.iter() {
+                lists.push((InstanceList::Primitives(name.clone(), material), instances));
+            }
+        }
+        lists
+    }
+
+    fn instance_list_mut(&mut self, list: &InstanceList) -> Option<&mut Vec<Instance>> {
+        match list {
+            InstanceList::Cylinders => Some(&mut self.cylinder_instances),
+            InstanceList::Cones(taper) => self.cone_instances.get_mut(taper),
+            InstanceList::Spheres => Some(&mut self.sphere_instances),
+            InstanceList::Circles => Some(&mut self.circle_instances),
+            InstanceList::Primitives(name, material) => self
+                .primitive_instances
+                .get_mut(name)
+                .and_then(|primitive| primitive.get_mut(material)),
+        }
+    }
+
+    /// The number of bytes occupied by the interpretation's instances and surfaces.
+    pub fn memory_usage(&self) -> usize {
+        self.instance_lists()
+            .iter()
+            .map(|(_, instances)| instances.len() * size_of::<Instance>())
+            .sum::<usize>()
+            + self.surfaces.memory_usage()
+    }
+
+    /// Writes the interpretation's instances into a binary blob.
+    /// Queries are not written since they have already been answered when an iteration is cached.
+    pub fn encode(&self, encoder: &mut Encoder) {
+        encoder.slice(&[self.aabb.min().to_array(), self.aabb.max().to_array()]);
+        encoder.slice(&self.cylinder_instances);
+        encoder.u64(self.cone_instances.len() as u64);
+        for (taper, instances) in self.cone_instances.iter() {
+            encoder.u32(*taper);
+            encoder.slice(instances);
+        }
+        encoder.slice(&self.sphere_instances);
+        encoder.slice(&self.circle_instances);
+        encoder.u64(self.primitive_instances.len() as u64);
+        for (name, instances) in self.primitive_instances.iter() {
+            encoder.str(name);
+            encoder.u64(instances.len() as u64);
+            for (material, instances) in instances.iter() {
+                encoder.u64(*material as u64);
+                encoder.slice(instances);
+            }
+        }
+        self.surfaces.encode(encoder);
+    }
+
+    pub fn decode(decoder: &mut Decoder) -> Option<Self> {
+        let aabb = match decoder.slice::<[f32; 3]>()?.as_slice() {
+            [min, max] => Bounds3::new(Vec3::from(*min), Vec3::from(*max)),
+            _ => return None,
+        };
+        let cylinder_instances = decoder.slice()?;
+        let mut cone_instances = HashMap::new();
+        for _ in 0..decoder.usize()? {
+            let taper = decoder.u32()?;
+            cone_instances.insert(taper, decoder.slice()?);
+        }
+        let sphere_instances = decoder.slice()?;
+        let circle_instances = decoder.slice()?;
+        let mut primitive_instances = HashMap::new();
+        for _ in 0..decoder.usize()? {
+            let name = decoder.str()?.to_string();
+            let mut instances = HashMap::new();
+            for _ in 0..decoder.usize()? {
+                let material = decoder.usize()?;
+                instances.insert(material, decoder.slice()?);
+            }
+            primitive_instances.insert(name, instances);
+        }
+        Some(Self {
+            aabb,
+            cylinder_instances,
+            cone_instances,
+            sphere_instances,
+            circle_instances,
+            primitive_instances,
+            surfaces: SurfaceMesh::decode(decoder)?,
+            queries: Vec::new(),
+        })
+    }
+}
+
+// identifies a list of instances of a `TurtleInterpretation`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum InstanceList {
+    Cylinders,
+    Cones(u32),
+    Spheres,
+    Circles,
+    Primitives(String, usize),
+}
+
+impl InstanceList {
+    // scales an instance that has no counterpart at the start of its iteration's growth
+    fn grow(&self, matrix: Mat4, growth: f32) -> Mat4 {
+        match self {
+            // cylinders and cones are oriented along their local y axis
+            InstanceList::Cylinders | InstanceList::Cones(_) => scale_length(matrix, growth),
+            _ => matrix.mul_mat4(&Mat4::from_scale(Vec3::splat(growth))),
+        }
+    }
+}
+
+fn lerp_matrix(a: Mat4, b: Mat4, t: f32) -> Mat4 {
+    Mat4::from_cols(
+        a.x_axis.lerp(b.x_axis, t),
+        a.y_axis.lerp(b.y_axis, t),
+        a.z_axis.lerp(b.z_axis, t),
+        a.w_axis.lerp(b.w_axis, t),
+    )
+}
+
+// scales a cylinder along its local y axis
+fn scale_length(matrix: Mat4, length: f32) -> Mat4 {
+    matrix.mul_mat4(&Mat4::from_scale(Vec3::new(1., length, 1.)))
+}
+
+// the lineage of each instance and the number of instances of the same lineage before it
+fn lineage_occurrences(instances: &[Instance]) -> Vec<(u32, u32)> {
+    let mut occurrences = HashMap::new();
+    instances
+        .iter()
+        .map(|c| {
+            let id = c.lineage().id();
+            let occurrence = occurrences.entry(id).or_insert(0);
+            *occurrence += 1;
+            (id, *occurrence - 1)
+        })
+        .collect()
+}
+
+// scales and centers a model as if it had the given bounds
+fn model_transform(l_system_transform: Transform, bounds: Bounds3) -> Mat4 {
+    let scale_value = 1. / bounds.diagonal().max_element();
+    l_system_transform
+        .as_mat4()
+        .mul_mat4(&Mat4::from_scale(Vec3::new(
+            scale_value,
+            scale_value,
+            scale_value,
+        )))
+        .mul_mat4(&Mat4::from_translation(-bounds.center()))
+}
+
+/// The interpretations of an iteration at the start and at the end of its growth (see
+/// `TimedSnapshot::growth`).
+///
+/// Each instance at the end is matched with the instance of the same lineage at the start, s.t.
+/// the growth can be animated by interpolating their transforms instead of interpreting the
+/// iteration again at each point in time.
+/// Surfaces can't be interpolated and are always taken from the end.
+pub struct GrowthKeyframes {
+    end: TurtleInterpretation,
+    // the transform at the start of each instance at the end, or `None` if there is no instance of
+    // the same lineage at the start, i.e., if the homomorphism created it only later on
+    start: HashMap<InstanceList, Vec<Option<Mat4>>>,
+}
+
+impl GrowthKeyframes {
+    pub fn new(start: TurtleInterpretation, end: TurtleInterpretation) -> Self {
+        let start_lists: HashMap<InstanceList, &Vec<Instance>> =
+            start.instance_lists().into_iter().collect();
+        let start = end
+            .instance_lists()
+            .into_iter()
+            .map(|(list, instances)| {
+                let mut start_matrices: HashMap<(u32, u32), Mat4> = match start_lists.get(&list) {
+                    Some(start_instances) => lineage_occurrences(start_instances)
+                        .into_iter()
+                        .zip(start_instances.iter().map(|i| i.matrix()))
+                        .collect(),
+                    None => HashMap::new(),
+                };
+                let matrices = lineage_occurrences(instances)
+                    .iter()
+                    .map(|key| start_matrices.remove(key))
+                    .collect();
+                (list, matrices)
+            })
+            .collect();
+        Self { end, start }
+    }
+
+    // interpolates the instances of the given list at the given progress in `[0;1]` and applies
+    // the given transform to them
+    fn interpolate_into(
+        &self,
+        list: &InstanceList,
+        instances: &[Instance],
+        progress: f32,
+        transform: Mat4,
+        interpolated: &mut Vec<Instance>,
+    ) {
+        interpolated.clear();
+        let start = match self.start.get(list) {
+            Some(start) => start,
+            None => return,
+        };
+        interpolated.extend(instances.iter().zip(start.iter()).map(|(instance, start)| {
+            let matrix = match start {
+                Some(start) => lerp_matrix(*start, instance.matrix(), progress),
+                None => list.grow(instance.matrix(), progress),
+            };
+            let mut interpolated = *instance;
+            interpolated.set_matrix(transform.mul_mat4(&matrix));
+            interpolated
+        }));
+    }
+
+    /// Returns the interpretation at the given progress in `[0;1]` of the iteration's growth.
+    pub fn interpolate(&self, progress: f32) -> TurtleInterpretation {
+        let progress = progress.clamp(0., 1.);
+        let mut interpretation = self.end.clone();
+        let mut interpolated = Vec::new();
+        for (list, instances) in self.end.instance_lists() {
+            self.interpolate_into(
+                &list,
+                instances,
+                progress,
+                Mat4::IDENTITY,
+                &mut interpolated,
+            );
+            if let Some(instances) = interpretation.instance_list_mut(&list) {
+                std::mem::swap(instances, &mut interpolated);
+            }
+        }
+        interpretation
+    }
+
+    /// The number of bytes occupied by both keyframes.
+    pub fn memory_usage(&self) -> usize {
+        self.end.memory_usage()
+            + self
+                .start
+                .values()
+                .map(|matrices| matrices.len() * size_of::<Option<Mat4>>())
+                .sum::<usize>()
+    }
+}
+
+/// Interprets a list of turtle commands in chunks, s.t. interpreting a long list can be spread
+/// over multiple calls and abandoned at any point.
+/// The turtle's stack, the bounds and all instances created so far are kept between calls.
+pub struct TurtleInterpreter {
+    aabb: Bounds3,
+    cylinder_instances: Vec<Instance>,
+    // cones grouped by their taper (see `CONE_TAPERS`)
+    cone_instances: HashMap<u32, Vec<Instance>>,
+    sphere_instances: Vec<Instance>,
+    circle_instances: Vec<Instance>,
+    primitive_instances: HashMap<String, HashMap<usize, Vec<Instance>>>,
+    surfaces: SurfaceMesh,
+    queries: Vec<TurtleQuery>,
+    stack: VecDeque<TurtleState>,
+    // the vertices of all polygons that have been started but not yet completed, and the lineage
+    // of the modules that started them
+    open_polygons: Vec<(Vec<Vec3>, Lineage)>,
+    // the rings, material and lineage of each generalized cylinder
+    tubes: Vec<(Vec<TubeRing>, Material, Lineage)>,
+    generalized_cylinders: bool,
+    joint_spheres: bool,
+    state: TurtleState,
+    primitives: HashMap<String, LSystemPrimitive>,
+    tropism: Option<Tropism>,
+    random: Random,
+    commands_interpreted: usize,
+}
+
+impl TurtleInterpreter {
+    pub fn new(
         l_system_transform: Transform,
         initial_material_state: MaterialState,
-        primitives: &HashMap<String, LSystemPrimitive>,
+        primitives: HashMap<String, LSystemPrimitive>,
         world_tropism: &Option<Tropism>,
-        gpu: &Arc<Gpu>,
+        random: Random,
+        generalized_cylinders: bool,
+        joint_spheres: bool,
     ) -> Self {
-        let mut aabb = Bounds3::new(Vec3::ZERO, Vec3::ZERO);
-        let mut cylinder_instances: Vec<Instance> = Vec::new();
-        let mut primitive_instances: HashMap<String, HashMap<usize, Vec<Instance>>> =
-            HashMap::new();
-
-        let mut stack = VecDeque::new();
-        let mut state = TurtleState {
-            material_state: initial_material_state,
-            ..Default::default()
-        };
-
         // the L-system might specify a tropism in the L-system's local space
         // -> transform it to the turtle's local space (a child of the L-system's space)
         let tropism = world_tropism.as_ref().map(|world_tropism| Tropism {
@@ -180,20 +516,99 @@ impl LSystemModel {
                 .truncate(),
             e: world_tropism.e,
         });
+        Self {
+            aabb: Bounds3::new(Vec3::ZERO, Vec3::ZERO),
+            cylinder_instances: Vec::new(),
+            cone_instances: HashMap::new(),
+            sphere_instances: Vec::new(),
+            circle_instances: Vec::new(),
+            primitive_instances: HashMap::new(),
+            surfaces: SurfaceMesh::default(),
+            queries: Vec::new(),
+            stack: VecDeque::new(),
+            open_polygons: Vec::new(),
+            tubes: Vec::new(),
+            generalized_cylinders,
+            joint_spheres,
+            state: TurtleState {
+                material_state: initial_material_state,
+                ..Default::default()
+            },
+            primitives,
+            tropism,
+            random,
+            commands_interpreted: 0,
+        }
+    }
 
+    /// The number of commands interpreted so far.
+    pub fn commands_interpreted(&self) -> usize {
+        self.commands_interpreted
+    }
+
+    /// Returns the generalized cylinder the turtle extends.
+    /// Starts a new one at the turtle's position if the turtle doesn't extend one or if the turtle's
+    /// material changed since.
+    fn current_tube(&mut self, radius: f32, lineage: Lineage) -> usize {
+        let fixed_material = self.state.fixed_material();
+        match self.state.tube {
+            Some(tube) if fixed_material.is_none_or(|m| m == self.tubes[tube].1) => tube,
+            _ => {
+                let material =
+                    fixed_material.unwrap_or_else(|| self.state.get_material(&mut self.random));
+                let start = TubeRing::new(self.state.transform.position(), radius);
+                self.grow_aabb_by_ring(start);
+                self.tubes.push((vec![start], material, lineage));
+                self.state.tube = Some(self.tubes.len() - 1);
+                self.tubes.len() - 1
+            }
+        }
+    }
+
+    // bends the turtle's heading towards the tropism's direction, if there is one
+    fn apply_tropism(&mut self) {
+        if let Some(t) = self.tropism {
+            let t = Tropism {
+                e: self.state.tropism_strength.unwrap_or(t.e),
+                ..t
+            };
+            self.state
+                .set_forward(t.corrected_forward(self.state.transform.orientation()));
+        }
+    }
+
+    fn grow_aabb_by_ring(&mut self, ring: TubeRing) {
+        self.aabb.grow(ring.position() - Vec3::splat(ring.radius()));
+        self.aabb.grow(ring.position() + Vec3::splat(ring.radius()));
+    }
+
+    /// Interprets the given commands as a continuation of all commands interpreted before, where
+    /// each command is paired with the lineage of the module it was created from.
+    pub fn interpret<'a>(
+        &mut self,
+        commands: impl IntoIterator<Item = (TurtleCommand<'a>, Lineage)>,
+    ) {
         // the base cylinder mesh is oriented along the y axis but the turtle is oriented along the z axis
         let cylinder_base_rotation = Quat::from_rotation_x(f32::to_radians(-90.));
-        let cylinder_aabb = Bounds3::new(Vec3::new(-0.5, 0.0, -0.5), Vec3::new(0.5, 1.0, 0.5));
+        // the corners are computed once, s.t. interpreting a command doesn't allocate
+        let cylinder_corners =
+            Bounds3::new(Vec3::new(-0.5, 0.0, -0.5), Vec3::new(0.5, 1.0, 0.5)).corners();
+        let sphere_corners = Bounds3::new(-Vec3::ONE, Vec3::ONE).corners();
+        let circle_corners =
+            Bounds3::new(Vec3::new(-0.5, 0.0, -0.5), Vec3::new(0.5, 0.0, 0.5)).corners();
 
-        for c in commands {
-            if state.ignoring_branch_depth > 0 {
+        for (c, lineage) in commands {
+            let c = &c;
+            let i = self.commands_interpreted;
+            self.commands_interpreted += 1;
+            if self.state.ignoring_branch_depth > 0 {
                 match c {
                     TurtleCommand::PushToStack => {
-                        state.ignoring_branch_depth += 1;
+                        self.state.ignoring_branch_depth += 1;
                     }
                     TurtleCommand::PopFromStack => {
-                        state.ignoring_branch_depth -= 1;
-                        if state.ignoring_branch_depth > 0 {
+                        self.state.ignoring_branch_depth -= 1;
+                        if self.state.ignoring_branch_depth > 0 {
                             continue;
                         }
                     }
@@ -201,112 +616,181 @@ impl LSystemModel {
                 }
             }
             match c {
+                TurtleCommand::AddCylinder(cylinder) if self.generalized_cylinders => {
+                    let length = cylinder.length() * self.state.length_scale;
+                    let diameter = cylinder.diameter(self.state.default_cylinder_diameter);
+                    // as thick as a cylinder segment, whose mesh has a radius of 0.5 and is scaled
+                    // by half the segment's diameter
+                    let tube = self.current_tube(diameter * self.state.width_scale * 0.25, lineage);
+                    self.state.transform.move_forward(length);
+                    let end = TubeRing::new(
+                        self.state.transform.position(),
+                        cylinder.end_diameter(diameter) * self.state.width_scale * 0.25,
+                    );
+                    self.grow_aabb_by_ring(end);
+                    self.tubes[tube].0.push(end);
+                    self.apply_tropism();
+                }
                 TurtleCommand::AddCylinder(cylinder) => {
-                    let radius = cylinder.diameter(state.default_cylinder_diameter) * 0.5;
-                    let scale_vec = Vec3::new(radius, cylinder.length(), radius);
-                    let cylinder_transform =
-                        Transform::from_scale_rotation(scale_vec, cylinder_base_rotation);
-                    let instance_transform =
-                        state.transform().as_mat4_with_child(&cylinder_transform);
+                    let length = cylinder.length() * self.state.length_scale;
+                    let diameter = cylinder.diameter(self.state.default_cylinder_diameter);
+                    let radius = diameter * self.state.width_scale * 0.5;
+                    let end_radius = cylinder.end_diameter(diameter) * self.state.width_scale * 0.5;
+                    let segment_transform = if end_radius <= radius {
+                        Transform::from_scale_rotation(
+                            Vec3::new(radius, length, radius),
+                            cylinder_base_rotation,
+                        )
+                    } else {
+                        // cone meshes narrow towards their top, so widening cones are drawn from
+                        // their end towards their start
+                        Transform::from_scale_rotation_translation(
+                            Vec3::new(end_radius, length, end_radius),
+                            Quat::from_rotation_x(f32::to_radians(90.)),
+                            cylinder_base_rotation.mul_vec3(Vec3::Y) * length,
+                        )
+                    };
+                    let instance_transform = self
+                        .state
+                        .transform()
+                        .as_mat4_with_child(&segment_transform);
 
-                    for c in cylinder_aabb.corners() {
-                        aabb.grow(instance_transform.transform_point3(c));
+                    for &c in cylinder_corners.iter() {
+                        self.aabb.grow(instance_transform.transform_point3(c));
                     }
 
-                    cylinder_instances
-                        .push(Instance::new(instance_transform, state.get_material()));
+                    let mut instance = Instance::new(
+                        instance_transform,
+                        self.state.get_material(&mut self.random),
+                    );
+                    instance.set_lineage(lineage);
+                    if end_radius == radius {
+                        self.cylinder_instances.push(instance);
+                    } else {
+                        let taper = (radius.min(end_radius) / radius.max(end_radius)
+                            * CONE_TAPERS as f32)
+                            .round() as u32;
+                        self.cone_instances.entry(taper).or_default().push(instance);
+                    }
 
-                    state.transform.move_forward(cylinder.length());
+                    self.state.transform.move_forward(length);
 
-                    if let Some(t) = tropism {
-                        state.set_forward(t.corrected_forward(state.transform.orientation()));
+                    if self.joint_spheres {
+                        // the sphere mesh has a radius of 1 and the cylinder mesh one of 0.5
+                        let joint_transform =
+                            self.state
+                                .transform()
+                                .as_mat4_with_child(&Transform::from_scale(Vec3::splat(
+                                    end_radius * 0.5,
+                                )));
+                        for &c in sphere_corners.iter() {
+                            self.aabb.grow(joint_transform.transform_point3(c));
+                        }
+                        instance.set_matrix(joint_transform);
+                        self.sphere_instances.push(instance);
                     }
+                    self.apply_tropism();
                 }
                 TurtleCommand::MoveForward(t) => {
-                    state.transform.move_forward(t.length());
+                    self.state
+                        .transform
+                        .move_forward(t.length() * self.state.length_scale);
+                    self.state.tube = None;
                 }
                 TurtleCommand::RotateYaw(yaw) => {
-                    state.transform.yaw_deg(yaw.angle());
+                    self.state.transform.yaw_deg(yaw.angle());
                 }
                 TurtleCommand::RotateYawNegative(yaw) => {
-                    state.transform.yaw_deg(-yaw.angle());
+                    self.state.transform.yaw_deg(-yaw.angle());
                 }
                 TurtleCommand::RotatePitch(pitch) => {
-                    state.transform.pitch_deg(pitch.angle());
+                    self.state.transform.pitch_deg(pitch.angle());
                 }
                 TurtleCommand::RotatePitchNegative(pitch) => {
-                    state.transform.pitch_deg(-pitch.angle());
+                    self.state.transform.pitch_deg(-pitch.angle());
                 }
                 TurtleCommand::RotateRoll(roll) => {
-                    state.transform.roll_deg(roll.angle());
+                    self.state.transform.roll_deg(roll.angle());
                 }
                 TurtleCommand::RotateRollNegative(roll) => {
-                    state.transform.roll_deg(-roll.angle());
+                    self.state.transform.roll_deg(-roll.angle());
                 }
                 TurtleCommand::Yaw180 => {
-                    state.transform.yaw_deg(180.);
+                    self.state.transform.yaw_deg(180.);
                 }
                 TurtleCommand::PushToStack => {
-                    stack.push_front(state.clone());
+                    self.stack.push_front(self.state.clone());
+                    // a branch starts its own tube, s.t. the parent's tube continues unaffected
+                    self.state.tube = None;
                 }
                 TurtleCommand::PopFromStack => {
-                    state = stack
+                    self.state = self
+                        .stack
                         .pop_front()
                         .expect("Invalid PopFromStack command: empty stack");
                 }
                 TurtleCommand::ToUpPlane => {
-                    state.rotate_towards_up_plane();
+                    self.state.rotate_towards_up_plane();
                 }
                 TurtleCommand::SetDefaultCylinderDiameter(set_default_cylinder_radius) => {
-                    state.set_default_cylinder_diameter(set_default_cylinder_radius.radius());
+                    self.state
+                        .set_default_cylinder_diameter(set_default_cylinder_radius.radius());
                 }
                 TurtleCommand::SetMaterialIndex(set_material_index) => {
-                    if !state.material_state.materials.is_empty() {
+                    if !self.state.material_state.materials.is_empty() {
                         let new_index = if let Some(index) = set_material_index.material_index() {
                             *index
                         } else {
-                            let current_index = match state.material_state.material_mode {
+                            let current_index = match self.state.material_state.material_mode {
                                 MaterialMode::MaterialIndex(i) => i,
                                 _ => 0,
                             };
                             current_index + 1
                         };
-                        state.material_state.material_mode = MaterialMode::MaterialIndex(new_index);
+                        self.state.material_state.material_mode =
+                            MaterialMode::MaterialIndex(new_index);
                     }
                 }
                 TurtleCommand::IgnoreRemainingBranch => {
-                    state.ignoring_branch_depth = 1;
+                    self.state.ignoring_branch_depth = 1;
                 }
                 TurtleCommand::AddPredefinedPrimitive(surface_command) => {
                     let surface_id = surface_command.name();
                     let surface_iteration = surface_command.iteration();
-                    if let Some(primitive) = primitives.get(surface_id) {
-                        if !primitive_instances.contains_key(surface_id) {
-                            primitive_instances.insert(surface_id.to_string(), HashMap::new());
+                    if let Some(primitive) = self.primitives.get(surface_id) {
+                        if !self.primitive_instances.contains_key(surface_id) {
+                            self.primitive_instances
+                                .insert(surface_id.to_string(), HashMap::new());
                         }
-                        if !primitive_instances
+                        if !self
+                            .primitive_instances
                             .get(surface_id)
                             .unwrap()
                             .contains_key(&surface_iteration)
                         {
-                            primitive_instances
+                            self.primitive_instances
                                 .get_mut(surface_id)
                                 .unwrap()
                                 .insert(surface_iteration, Vec::new());
                         }
 
-                        let instance_transform =
-                            state.transform().as_mat4_with_child(&primitive.transform());
+                        let instance_transform = self
+                            .state
+                            .transform()
+                            .as_mat4_with_child(&primitive.transform());
                         for c in primitive.aabb().corners() {
-                            aabb.grow(instance_transform.transform_point3(c));
+                            self.aabb.grow(instance_transform.transform_point3(c));
                         }
 
-                        let instance = Instance::new(
+                        let mut instance = Instance::new(
                             instance_transform,
-                            primitive.material.unwrap_or_else(|| state.get_material()),
+                            primitive
+                                .material
+                                .unwrap_or_else(|| self.state.get_material(&mut self.random)),
                         );
+                        instance.set_lineage(lineage);
 
-                        primitive_instances
+                        self.primitive_instances
                             .get_mut(surface_id)
                             .unwrap()
                             .get_mut(&surface_iteration)
@@ -314,33 +798,175 @@ impl LSystemModel {
                             .push(instance);
                     }
                 }
+                // polygons are not part of the turtle's state, s.t. a polygon can be completed in
+                // another branch than the one it was started in
                 TurtleCommand::BeginPolygon => {
-                    log::debug!("unhandled begin polygon command");
+                    self.open_polygons.push((Vec::new(), lineage));
+                }
+                TurtleCommand::EndPolygon => match self.open_polygons.pop() {
+                    Some((vertices, polygon_lineage)) => {
+                        let material = self.state.get_material(&mut self.random);
+                        if self
+                            .surfaces
+                            .add_polygon(&vertices, material, polygon_lineage)
+                        {
+                            for &v in vertices.iter() {
+                                self.aabb.grow(v);
+                            }
+                        }
+                    }
+                    None => log::debug!("encountered end polygon command without open polygon"),
+                },
+                TurtleCommand::MoveAlongEdge(t) => {
+                    self.state
+                        .transform
+                        .move_forward(t.length() * self.state.length_scale);
+                    self.state.tube = None;
+                }
+                TurtleCommand::ScaleLength(scale) => {
+                    self.state.length_scale *= scale.factor();
+                }
+                TurtleCommand::ScaleWidth(scale) => {
+                    self.state.width_scale *= scale.factor();
+                }
+                TurtleCommand::SetHeading(heading) => {
+                    if let Some(forward) = heading.heading().and_then(|h| h.try_normalize()) {
+                        // the up axis is kept as close as possible to the current one, unless it
+                        // is given explicitly
+                        let up = heading.up().unwrap_or(self.state.transform.up());
+                        let up = if forward.cross(up).length_squared() > f32::EPSILON {
+                            up
+                        } else {
+                            forward.any_orthonormal_vector()
+                        };
+                        self.state
+                            .transform
+                            .set_orientation(OrthonormalBasis::new(forward, up));
+                    }
+                }
+                TurtleCommand::SetTropismStrength(strength) => {
+                    self.state.tropism_strength = strength.strength();
                 }
-                TurtleCommand::EndPolygon => {
-                    log::debug!("unhandled end polygon command");
+                TurtleCommand::AddSphere(sphere) => {
+                    let diameter = sphere.diameter(self.state.default_cylinder_diameter)
+                        * self.state.width_scale;
+                    // as thick as a cylinder segment with the same diameter
+                    let instance_transform = self
+                        .state
+                        .transform()
+                        .as_mat4_with_child(&Transform::from_scale(Vec3::splat(diameter * 0.25)));
+                    for &c in sphere_corners.iter() {
+                        self.aabb.grow(instance_transform.transform_point3(c));
+                    }
+                    let mut instance = Instance::new(
+                        instance_transform,
+                        self.state.get_material(&mut self.random),
+                    );
+                    instance.set_lineage(lineage);
+                    self.sphere_instances.push(instance);
                 }
-                TurtleCommand::MoveAlongEdge(_) => {
-                    log::debug!("unhandled end polygon command");
+                TurtleCommand::AddCircle(circle) => {
+                    let diameter = circle.diameter(self.state.default_cylinder_diameter)
+                        * self.state.width_scale;
+                    // the circle mesh lies in the plane spanned by the turtle's head and right
+                    // axes and has a radius of 0.5, like the cylinder mesh
+                    let instance_transform =
+                        self.state
+                            .transform()
+                            .as_mat4_with_child(&Transform::from_scale(Vec3::new(
+                                diameter * 0.5,
+                                1.,
+                                diameter * 0.5,
+                            )));
+                    for &c in circle_corners.iter() {
+                        self.aabb.grow(instance_transform.transform_point3(c));
+                    }
+                    let mut instance = Instance::new(
+                        instance_transform,
+                        self.state.get_material(&mut self.random),
+                    );
+                    instance.set_lineage(lineage);
+                    self.circle_instances.push(instance);
                 }
                 TurtleCommand::RecordVertex => {
-                    log::debug!("unhandled record vertex command");
+                    if let Some((vertices, _)) = self.open_polygons.last_mut() {
+                        vertices.push(self.state.transform.position());
+                    }
+                }
+                TurtleCommand::Query => {
+                    self.queries.push(TurtleQuery {
+                        index: i,
+                        transform: self.state.transform(),
+                    });
                 }
                 TurtleCommand::Unknown => {
                     log::debug!("encountered unknown command");
                 }
             }
         }
+    }
 
-        let scale_value = 1. / aabb.diagonal().max_element();
-        let model_transform = l_system_transform
-            .as_mat4()
-            .mul_mat4(&Mat4::from_scale(Vec3::new(
-                scale_value,
-                scale_value,
-                scale_value,
-            )))
-            .mul_mat4(&Mat4::from_translation(-aabb.center()));
+    pub fn finish(mut self) -> TurtleInterpretation {
+        for (rings, material, lineage) in self.tubes.iter() {
+            self.surfaces.add_tube(rings, *material, *lineage);
+        }
+        TurtleInterpretation {
+            aabb: self.aabb,
+            cylinder_instances: self.cylinder_instances,
+            cone_instances: self.cone_instances,
+            sphere_instances: self.sphere_instances,
+            circle_instances: self.circle_instances,
+            primitive_instances: self.primitive_instances,
+            surfaces: self.surfaces,
+            queries: self.queries,
+        }
+    }
+}
+
+pub struct LSystemModel {
+    aabb: Bounds3,
+    cylinder_instances: Vec<Instance>,
+    // the lineage of each cylinder and the number of cylinders of the same lineage before it
+    cylinder_lineage: Vec<(u32, u32)>,
+    cylinder_instances_buffer: Buffer<Instance>,
+    cone_instances_buffers: HashMap<u32, Buffer<Instance>>,
+    sphere_instances_buffer: Option<Buffer<Instance>>,
+    circle_instances_buffer: Option<Buffer<Instance>>,
+    primitive_instances_buffers: HashMap<String, HashMap<usize, Buffer<Instance>>>,
+    surfaces: Option<GpuSurfaces>,
+}
+
+impl LSystemModel {
+    pub fn from_interpretation(
+        interpretation: TurtleInterpretation,
+        l_system_transform: Transform,
+        gpu: &Arc<Gpu>,
+    ) -> Self {
+        let bounds = interpretation.aabb;
+        Self::from_interpretation_in_bounds(interpretation, l_system_transform, bounds, gpu)
+    }
+
+    /// Creates a model that is scaled and centered as if it had the given bounds, e.g., to keep
+    /// the scale of a growing model consistent.
+    pub fn from_interpretation_in_bounds(
+        interpretation: TurtleInterpretation,
+        l_system_transform: Transform,
+        bounds: Bounds3,
+        gpu: &Arc<Gpu>,
+    ) -> Self {
+        let TurtleInterpretation {
+            aabb,
+            mut cylinder_instances,
+            cone_instances,
+            mut sphere_instances,
+            mut circle_instances,
+            mut primitive_instances,
+            surfaces,
+            ..
+        } = interpretation;
+
+        let cylinder_lineage = lineage_occurrences(&cylinder_instances);
+        let model_transform = model_transform(l_system_transform, bounds);
 
         cylinder_instances.iter_mut().for_each(|c| {
             c.set_matrix(model_transform.mul_mat4(&c.matrix()));
@@ -349,6 +975,29 @@ impl LSystemModel {
         let cylinder_instances_buffer =
             Buffer::from_data("", &cylinder_instances, BufferUsages::STORAGE, gpu);
 
+        let cone_instances_buffers = cone_instances
+            .into_iter()
+            .map(|(taper, mut instances)| {
+                instances.iter_mut().for_each(|c| {
+                    c.set_matrix(model_transform.mul_mat4(&c.matrix()));
+                });
+                (
+                    taper,
+                    Buffer::from_data("", &instances, BufferUsages::STORAGE, gpu),
+                )
+            })
+            .collect();
+
+        let create_optional_buffer = |instances: &mut Vec<Instance>| {
+            instances.iter_mut().for_each(|c| {
+                c.set_matrix(model_transform.mul_mat4(&c.matrix()));
+            });
+            (!instances.is_empty())
+                .then(|| Buffer::from_data("", instances, BufferUsages::STORAGE, gpu))
+        };
+        let sphere_instances_buffer = create_optional_buffer(&mut sphere_instances);
+        let circle_instances_buffer = create_optional_buffer(&mut circle_instances);
+
         let mut primitive_instances_buffers = HashMap::new();
         for (id, primitive) in primitive_instances.iter_mut() {
             let mut instances_buffers = HashMap::new();
@@ -367,8 +1016,92 @@ impl LSystemModel {
 
         Self {
             aabb,
+            cylinder_instances,
+            cylinder_lineage,
             cylinder_instances_buffer,
+            cone_instances_buffers,
+            sphere_instances_buffer,
+            circle_instances_buffer,
             primitive_instances_buffers,
+            surfaces: GpuSurfaces::new(surfaces, model_transform, gpu),
+        }
+    }
+
+    /// Blends the cylinders of two models at the given progress in `[0;1]`.
+    /// Cylinders of the same lineage are interpolated, cylinders only present in `to` grow in from
+    /// zero length, and cylinders only present in `from` shrink out.
+    pub fn blend_cylinders(from: &Self, to: &Self, progress: f32) -> Vec<Instance> {
+        let progress = progress.clamp(0., 1.);
+        let mut unmatched: HashMap<(u32, u32), usize> = from
+            .cylinder_lineage
+            .iter()
+            .enumerate()
+            .map(|(i, key)| (*key, i))
+            .collect();
+        let mut instances = Vec::with_capacity(to.cylinder_instances.len());
+        for (key, instance) in to.cylinder_lineage.iter().zip(to.cylinder_instances.iter()) {
+            let matrix = match unmatched.remove(key) {
+                Some(i) => lerp_matrix(
+                    from.cylinder_instances[i].matrix(),
+                    instance.matrix(),
+                    progress,
+                ),
+                None => scale_length(instance.matrix(), progress),
+            };
+            let mut blended = *instance;
+            blended.set_matrix(matrix);
+            instances.push(blended);
+        }
+        let mut shrinking: Vec<usize> = unmatched.into_values().collect();
+        shrinking.sort_unstable();
+        for i in shrinking {
+            let mut instance = from.cylinder_instances[i];
+            instance.set_matrix(scale_length(instance.matrix(), 1. - progress));
+            instances.push(instance);
+        }
+        instances
+    }
+
+    /// Updates the instances of a model created from the given keyframes to the given progress in
+    /// `[0;1]` of their iteration's growth without creating new buffers.
+    /// The model must have been created from `GrowthKeyframes::interpolate` with the same
+    /// transform and bounds.
+    pub fn update_growth(
+        &mut self,
+        keyframes: &GrowthKeyframes,
+        progress: f32,
+        l_system_transform: Transform,
+        bounds: Bounds3,
+    ) {
+        let progress = progress.clamp(0., 1.);
+        let model_transform = model_transform(l_system_transform, bounds);
+        let mut interpolated = Vec::new();
+        for (list, instances) in keyframes.end.instance_lists() {
+            keyframes.interpolate_into(
+                &list,
+                instances,
+                progress,
+                model_transform,
+                &mut interpolated,
+            );
+            let buffer = match &list {
+                InstanceList::Cylinders => {
+                    self.cylinder_instances.clone_from(&interpolated);
+                    Some(&self.cylinder_instances_buffer)
+                }
+                InstanceList::Cones(taper) => self.cone_instances_buffers.get(taper),
+                InstanceList::Spheres => self.sphere_instances_buffer.as_ref(),
+                InstanceList::Circles => self.circle_instances_buffer.as_ref(),
+                InstanceList::Primitives(name, material) => self
+                    .primitive_instances_buffers
+                    .get(name)
+                    .and_then(|buffers| buffers.get(material)),
+            };
+            if let Some(buffer) = buffer.filter(|b| b.num_elements() == interpolated.len()) {
+                if !interpolated.is_empty() {
+                    buffer.write_buffer(&interpolated);
+                }
+            }
         }
     }
 
@@ -376,11 +1109,168 @@ impl LSystemModel {
         self.aabb
     }
 
+    /// The number of bytes occupied by the model's instances on the CPU and the GPU.
+    pub fn memory_usage(&self) -> usize {
+        let cpu = self.cylinder_instances.len() * size_of::<Instance>()
+            + self.cylinder_lineage.len() * size_of::<(u32, u32)>();
+        let gpu = self.cylinder_instances_buffer.size()
+            + self
+                .cone_instances_buffers
+                .values()
+                .chain(self.sphere_instances_buffer.iter())
+                .chain(self.circle_instances_buffer.iter())
+                .map(|buffer| buffer.size())
+                .sum::<u64>()
+            + self
+                .primitive_instances_buffers
+                .values()
+                .flat_map(|buffers| buffers.values())
+                .map(|buffer| buffer.size())
+                .sum::<u64>();
+        let surfaces = self.surfaces.as_ref().map_or(0, |p| p.memory_usage());
+        cpu + gpu as usize + surfaces
+    }
+
+    /// Frees the model's GPU buffers.
+    pub fn destroy(&self) {
+        self.cylinder_instances_buffer.buffer().destroy();
+        for buffer in self
+            .cone_instances_buffers
+            .values()
+            .chain(self.sphere_instances_buffer.iter())
+            .chain(self.circle_instances_buffer.iter())
+        {
+            buffer.buffer().destroy();
+        }
+        for buffers in self.primitive_instances_buffers.values() {
+            for buffer in buffers.values() {
+                buffer.buffer().destroy();
+            }
+        }
+        if let Some(surfaces) = self.surfaces.as_ref() {
+            surfaces.destroy();
+        }
+    }
+
+    /// The model's cylinders, e.g., to look up the lineage of a rendered cylinder.
+    pub fn cylinder_instances(&self) -> &Vec<Instance> {
+        &self.cylinder_instances
+    }
+
     pub fn cylinder_instances_buffer(&self) -> &Buffer<Instance> {
         &self.cylinder_instances_buffer
     }
 
+    /// The model's cones grouped by their taper (see `cone_taper`).
+    pub fn cone_instances(&self) -> &HashMap<u32, Buffer<Instance>> {
+        &self.cone_instances_buffers
+    }
+
+    /// The model's spheres, e.g., at the joints of its segments, if it has any.
+    pub fn sphere_instances(&self) -> Option<&Buffer<Instance>> {
+        self.sphere_instances_buffer.as_ref()
+    }
+
+    /// The model's circles, if it has any.
+    pub fn circle_instances(&self) -> Option<&Buffer<Instance>> {
+        self.circle_instances_buffer.as_ref()
+    }
+
     pub fn primitive_instances(&self) -> &HashMap<String, HashMap<usize, Buffer<Instance>>> {
         &self.primitive_instances_buffers
     }
+
+    /// The model's polygons and generalized cylinders, if it has any.
+    pub fn surfaces(&self) -> Option<&GpuSurfaces> {
+        self.surfaces.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lindenmayer::{LSystem, LSystemDefinition};
+    use serde_json::json;
+
+    // interprets the axiom of an L-system consisting of turtle commands
+    fn interpret(axiom: &str) -> TurtleInterpretation {
+        let definition: LSystemDefinition = serde_json::from_value(json!({
+            "alphabet": [
+                "F(l)", "F(l,d,e)", "f(l)", "+(a)", "&(a)", "[", "]", "{", "}", ".", "@O(d)",
+                "@c(d)"
+            ],
+            "parameters": {},
+            "productions": [],
+            "axiom": axiom
+        }))
+        .unwrap();
+        let l_system = LSystem::new(&definition).unwrap();
+        let mut interpreter = TurtleInterpreter::new(
+            Transform::default(),
+            MaterialState::default(),
+            HashMap::new(),
+            &None,
+            Random::new(0),
+            false,
+            false,
+        );
+        interpreter.interpret(
+            l_system
+                .current_modules()
+                .iter()
+                .map(|m| (TurtleCommand::from(m), Lineage::UNKNOWN)),
+        );
+        interpreter.finish()
+    }
+
+    fn encode(interpretation: &TurtleInterpretation) -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        interpretation.encode(&mut encoder);
+        encoder.into_bytes()
+    }
+
+    // the instance lists of an interpretation as raw bytes, independently of their order
+    fn instance_lists(interpretation: &TurtleInterpretation) -> HashMap<InstanceList, Vec<u8>> {
+        interpretation
+            .instance_lists()
+            .into_iter()
+            .map(|(list, instances)| (list, bytemuck::cast_slice(instances).to_vec()))
+            .collect()
+    }
+
+    const AXIOM: &str = "F(1)[+(30)F(2,0.2,0.1)@O(0.5)][&(45)F(1,0.2,0.05)@c(1)]{.f(1).+(90)f(1).}";
+
+    #[test]
+    fn decodes_encoded_interpretations() {
+        let interpretation = interpret(AXIOM);
+        // every kind of instance is encoded
+        assert!(instance_lists(&interpretation).len() >= 4);
+        assert!(instance_lists(&interpretation)
+            .values()
+            .all(|instances| !instances.is_empty()));
+        let bytes = encode(&interpretation);
+        let decoded = TurtleInterpretation::decode(&mut Decoder::new(&bytes)).unwrap();
+        assert_eq!(instance_lists(&decoded), instance_lists(&interpretation));
+        assert_eq!(decoded.aabb.min(), interpretation.aabb.min());
+        assert_eq!(decoded.aabb.max(), interpretation.aabb.max());
+        assert!(!decoded.surfaces.is_empty());
+        let mut surfaces = (Encoder::new(), Encoder::new());
+        decoded.surfaces.encode(&mut surfaces.0);
+        interpretation.surfaces.encode(&mut surfaces.1);
+        assert_eq!(surfaces.0.into_bytes(), surfaces.1.into_bytes());
+    }
+
+    #[test]
+    fn rejects_truncated_and_corrupted_interpretations() {
+        let bytes = encode(&interpret(AXIOM));
+        for len in 0..bytes.len() {
+            assert!(TurtleInterpretation::decode(&mut Decoder::new(&bytes[..len])).is_none());
+        }
+        // the bounds must consist of exactly two corners
+        let mut encoder = Encoder::new();
+        encoder.slice(&[[0f32; 3]; 3]);
+        let mut bytes = encoder.into_bytes();
+        bytes.extend_from_slice(&encode(&interpret("F(1)"))[8 + 2 * 12..]);
+        assert!(TurtleInterpretation::decode(&mut Decoder::new(&bytes)).is_none());
+    }
 }
@@ -3,12 +3,16 @@ use crate::framework::context::{ContextDescriptor, Gpu, SurfaceContext};
 use crate::framework::event::lifecycle::{OnCommandsSubmitted, PrepareRender, Update};
 #[cfg(target_arch = "wasm32")]
 use crate::framework::event::web::{
-    dispatch_canvas_event, register_custom_canvas_event_dispatcher,
+    dispatch_canvas_event, dispatch_canvas_event_with_data, register_custom_canvas_event_dispatcher,
 };
 use crate::framework::event::window::{OnResize, OnUserEvent, OnWindowEvent};
 use crate::framework::input::Input;
 use crate::lindenmayer::LSystem;
+#[cfg(target_arch = "wasm32")]
+use crate::lsystemrenderer::event::CacheEvent;
 use crate::lsystemrenderer::event::{LSystemEvent, SceneEvent, UiEvent};
+#[cfg(target_arch = "wasm32")]
+use crate::lsystemrenderer::l_system_manager::evaluator::CacheRequest;
 use crate::lsystemrenderer::renderer::Renderer;
 use crate::lsystemrenderer::scene::LSystemScene;
 use crate::lsystemrenderer::scene_descriptor::LSystemSceneDescriptor;
@@ -83,6 +87,7 @@ impl GpuApp for App {
             );
             register_custom_canvas_event_dispatcher("ui::scene::new", &canvas, event_loop);
             register_custom_canvas_event_dispatcher("ui::lsystem::iteration", &canvas, event_loop);
+            register_custom_canvas_event_dispatcher("ui::lsystem::cache", &canvas, event_loop);
             if dispatch_canvas_event("app::initialized", &canvas).is_err() {
                 log::error!("Could not dispatch 'app::initialized' event");
             }
@@ -131,6 +136,10 @@ impl OnUserEvent for App {
                 self.scene
                     .set_target_iteration(iteration.object_name(), iteration.iteration());
             }
+            #[cfg(target_arch = "wasm32")]
+            UiEvent::LSystem(LSystemEvent::Cache(cache)) => {
+                self.scene.receive_cache(cache.key(), cache.data());
+            }
             UiEvent::Scene(SceneEvent::BackgroundColor(color)) => {
                 self.scene.set_background_color(*color);
             }
@@ -153,6 +162,47 @@ impl OnWindowEvent for App {
 impl Update for App {
     fn update(&mut self, input: &Input) {
         self.scene.update(input);
+        #[cfg(target_arch = "wasm32")]
+        for progress in self.scene.take_evaluation_progress() {
+            if let Some(canvas) = &self.canvas {
+                if dispatch_canvas_event_with_data("app::lsystem::progress", &progress, canvas)
+                    .is_err()
+                {
+                    log::error!("Could not dispatch 'app::lsystem::progress' event");
+                }
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        if let Some(memory_usage) = self.scene.take_memory_usage() {
+            if let Some(canvas) = &self.canvas {
+                if dispatch_canvas_event_with_data("app::memory", &memory_usage, canvas).is_err() {
+                    log::error!("Could not dispatch 'app::memory' event");
+                }
+            }
+        }
+        // only the page displays the progress and memory usage
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.scene.take_evaluation_progress();
+            self.scene.take_memory_usage();
+        }
+        #[cfg(target_arch = "wasm32")]
+        for request in self.scene.take_cache_requests() {
+            let (event_type, cache) = match request {
+                CacheRequest::Load(key) => {
+                    ("app::lsystem::cache::load", CacheEvent::new(key, None))
+                }
+                CacheRequest::Store(key, data) => (
+                    "app::lsystem::cache::store",
+                    CacheEvent::new(key, Some(data)),
+                ),
+            };
+            if let Some(canvas) = &self.canvas {
+                if dispatch_canvas_event_with_data(event_type, &cache, canvas).is_err() {
+                    log::error!("Could not dispatch '{}' event", event_type);
+                }
+            }
+        }
     }
 }
 
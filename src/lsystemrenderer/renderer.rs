@@ -11,13 +11,14 @@ use crate::lsystemrenderer::scene::LSystemScene;
 use glam::{Mat4, Vec3, Vec4};
 use std::borrow::Cow;
 use std::mem;
+use std::ops::Range;
 use std::sync::Arc;
 use wgpu::Face::Back;
 use wgpu::{
     BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
     BindGroupLayoutEntry, BindingType, BufferBindingType, BufferUsages, Color, CommandEncoder,
-    CompareFunction, DepthStencilState, Extent3d, FragmentState, Label, LoadOp, Operations,
-    PipelineLayoutDescriptor, PrimitiveState, RenderPass, RenderPassColorAttachment,
+    CompareFunction, DepthStencilState, Extent3d, FragmentState, IndexFormat, Label, LoadOp,
+    Operations, PipelineLayoutDescriptor, PrimitiveState, RenderPass, RenderPassColorAttachment,
     RenderPassDepthStencilAttachment, RenderPassDescriptor, RenderPipeline,
     RenderPipelineDescriptor, ShaderModuleDescriptor, ShaderSource, ShaderStages,
     SurfaceConfiguration, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
@@ -27,6 +28,8 @@ use wgpu::{
 pub struct RenderObject {
     gpu_mesh: Arc<GpuMesh>,
     num_instances: u32,
+    // if set, each instance only draws its range of the mesh's indices
+    index_ranges: Option<Vec<Range<u32>>>,
     bind_group_index: u32,
     bind_group: BindGroup,
 }
@@ -34,7 +37,17 @@ pub struct RenderObject {
 impl Draw for RenderObject {
     fn draw<'a>(&'a self, pass: &mut RenderPass<'a>) {
         pass.set_bind_group(self.bind_group_index, &self.bind_group, &[]);
-        self.gpu_mesh.draw_instanced(pass, self.num_instances);
+        match self.index_ranges.as_ref() {
+            Some(index_ranges) => {
+                pass.set_index_buffer(self.gpu_mesh.index_buffer().slice(..), IndexFormat::Uint32);
+                pass.set_vertex_buffer(0, self.gpu_mesh.vertex_buffer().slice(..));
+                for (instance, range) in index_ranges.iter().enumerate() {
+                    let instance = instance as u32;
+                    pass.draw_indexed(range.clone(), 0, instance..instance + 1);
+                }
+            }
+            None => self.gpu_mesh.draw_instanced(pass, self.num_instances),
+        }
     }
 }
 
@@ -68,10 +81,25 @@ impl RenderObjectBuilder {
         RenderObject {
             gpu_mesh: mesh.clone(),
             num_instances: instances.num_elements() as u32,
+            index_ranges: None,
             bind_group_index: self.bind_group_index,
             bind_group,
         }
     }
+
+    /// Builds a render object that draws the given range of the mesh's indices with each instance.
+    pub fn build_with_index_ranges(
+        &self,
+        mesh: &Arc<GpuMesh>,
+        transform: &Buffer<ModelTransform>,
+        instances: &Buffer<Instance>,
+        index_ranges: &[Range<u32>],
+    ) -> RenderObject {
+        RenderObject {
+            index_ranges: Some(index_ranges.to_vec()),
+            ..self.build(mesh, transform, instances)
+        }
+    }
 }
 
 #[repr(C)]
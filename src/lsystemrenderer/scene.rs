@@ -10,20 +10,46 @@ use crate::framework::scene::light::LightSource;
 use crate::framework::scene::transform::Transform;
 use crate::lindenmayer::LSystem;
 use crate::lsystemrenderer::camera::OrbitCamera;
+use crate::lsystemrenderer::event::{EvaluationProgressEvent, MemoryUsageEvent};
 use crate::lsystemrenderer::instancing::{Instance, ModelTransform};
-use crate::lsystemrenderer::l_system_manager::turtle::LSystemPrimitive;
+use crate::lsystemrenderer::l_system_manager::environment::{Environment, EnvironmentHandle};
+#[cfg(target_arch = "wasm32")]
+use crate::lsystemrenderer::l_system_manager::evaluator::CacheRequest;
+use crate::lsystemrenderer::l_system_manager::turtle::{
+    cone_taper, LSystemModel, LSystemPrimitive,
+};
 use crate::lsystemrenderer::l_system_manager::{turtle::MaterialState, LSystemManager};
 use crate::lsystemrenderer::renderer::{
     LightSourcesBindGroup, LightSourcesBindGroupBuilder, RenderObject, RenderObjectBuilder,
 };
 use crate::lsystemrenderer::scene_descriptor::{
-    LSystemSceneDescriptor, SceneObjectDescriptor, SceneResource,
+    GrowthAnimationDescriptor, LSystemDescriptor, LSystemInstance, LSystemObjectDescriptor,
+    LSystemSceneDescriptor, SceneObjectDescriptor, SceneResource, TransitionDescriptor,
 };
-use glam::Vec3;
+use glam::{Mat4, Vec3};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use wgpu::BufferUsages;
 
+/// The default memory budget for the models of evaluated L-system iterations in megabytes.
+const DEFAULT_MEMORY_BUDGET: f32 = 1024.;
+
+/// The names of meshes that may be used as primitives or scene objects without defining a resource.
+/// Resources with the same name take precedence.
+const BUILTIN_PRIMITIVES: [&str; 6] = ["sphere", "cylinder", "cone", "disk", "torus", "capsule"];
+
+fn create_builtin_mesh(name: &str) -> Option<Mesh<Vertex>> {
+    match name {
+        "sphere" => Some(Mesh::new_icosphere(3)),
+        "cylinder" => Some(Mesh::new_cylinder(32, 1, false)),
+        "cone" => Some(Mesh::new_cone(32, 1, false)),
+        "disk" => Some(Mesh::new_disk(32)),
+        "torus" => Some(Mesh::new_torus(16, 32, 0.375, 0.125)),
+        "capsule" => Some(Mesh::new_capsule(32, 8, 0.25, 0.5)),
+        _ => None,
+    }
+}
+
 struct MeshResource {
     mesh: Arc<GpuMesh>,
     transform: Transform,
@@ -51,18 +77,174 @@ impl Resource {
     }
 }
 
+/// The meshes segments of L-system models are drawn with.
+struct SegmentMeshes {
+    cylinder: Arc<GpuMesh>,
+    sphere: Arc<GpuMesh>,
+    circle: Arc<GpuMesh>,
+    // cone meshes by their taper, each is created when it is first used
+    cones: HashMap<u32, Arc<GpuMesh>>,
+    gpu: Arc<Gpu>,
+}
+
+impl SegmentMeshes {
+    fn new(gpu: &Arc<Gpu>) -> Self {
+        Self {
+            cylinder: Arc::new(GpuMesh::from_mesh::<Vertex>(
+                &Mesh::new_default_cylinder(true),
+                gpu.device(),
+            )),
+            sphere: Arc::new(GpuMesh::from_mesh::<Vertex>(
+                &Mesh::new_icosphere(2),
+                gpu.device(),
+            )),
+            circle: Arc::new(GpuMesh::from_mesh::<Vertex>(
+                &Mesh::new_disk(32),
+                gpu.device(),
+            )),
+            cones: HashMap::new(),
+            gpu: gpu.clone(),
+        }
+    }
+
+    fn get_or_create_cone(&mut self, taper: u32) -> &Arc<GpuMesh> {
+        let gpu = &self.gpu;
+        self.cones.entry(taper).or_insert_with(|| {
+            Arc::new(GpuMesh::from_mesh::<Vertex>(
+                &Mesh::new_truncated_cone(32, 1, 0.5, 0.5 * cone_taper(taper), true),
+                gpu.device(),
+            ))
+        })
+    }
+}
+
 struct SceneMesh {
     mesh: Arc<GpuMesh>,
     instance_buffer: Buffer<Instance>,
     render_objects: Option<Vec<RenderObject>>,
 }
 
-struct LSystemObject {
+/// Identifies an `LSystemManager` in a scene.
+/// Objects referring to the same L-system instance share one manager unless they override its seed
+/// or parameters, or the instance is placed in an environment.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct LSystemManagerKey {
     system: String,
     instance: String,
+    seed: Option<u64>,
+    // parameter overrides sorted by name, values are stored as bits to make the key hashable
+    parameters: Vec<(String, u32)>,
+    // the object an instance in an environment is evaluated for, since the environment's answers
+    // depend on where the object is placed
+    object: Option<String>,
+}
+
+impl LSystemManagerKey {
+    fn new(system: &str, instance: &str, seed: Option<u64>) -> Self {
+        Self {
+            system: system.to_string(),
+            instance: instance.to_string(),
+            seed,
+            parameters: Vec::new(),
+            object: None,
+        }
+    }
+}
+
+/// Plays the development of an L-system object from its axiom to its target iteration.
+///
+/// The model is only created once per iteration from the keyframes of its growth (see
+/// `LSystemManager::growth_model`) and updated in place for each frame.
+struct GrowthAnimation {
+    iterations_per_second: f32,
+    repeat: bool,
+    start: Option<f32>,
+    time: Option<f32>,
+    // the iteration whose growth the model shows and the iteration whose bounds it is scaled to
+    shown: Option<(u32, u32)>,
+    model: Option<LSystemModel>,
+    render_objects: Option<Vec<RenderObject>>,
+}
+
+impl GrowthAnimation {
+    fn set_model(&mut self, model: LSystemModel) {
+        if let Some(old_model) = self.model.replace(model) {
+            old_model.destroy();
+        }
+        self.render_objects = None;
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.model.as_ref().map_or(0, |model| model.memory_usage())
+    }
+}
+
+impl Drop for GrowthAnimation {
+    fn drop(&mut self) {
+        if let Some(model) = self.model.as_ref() {
+            model.destroy();
+        }
+    }
+}
+
+impl From<&GrowthAnimationDescriptor> for GrowthAnimation {
+    fn from(descriptor: &GrowthAnimationDescriptor) -> Self {
+        Self {
+            iterations_per_second: descriptor.iterations_per_second(),
+            repeat: descriptor.repeat(),
+            start: None,
+            time: None,
+            shown: None,
+            model: None,
+            render_objects: None,
+        }
+    }
+}
+
+/// Blends between the previous and the new iteration of an L-system object when its iteration is
+/// changed.
+struct IterationTransition {
+    duration: f32,
+    from: Option<u32>,
+    start: Option<f32>,
+    cylinder_instances_buffer: Option<Buffer<Instance>>,
+    render_objects: Option<Vec<RenderObject>>,
+}
+
+impl IterationTransition {
+    fn set_cylinder_instances_buffer(&mut self, buffer: Option<Buffer<Instance>>) {
+        if let Some(old_buffer) = std::mem::replace(&mut self.cylinder_instances_buffer, buffer) {
+            old_buffer.buffer().destroy();
+        }
+        self.render_objects = None;
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.cylinder_instances_buffer
+            .as_ref()
+            .map_or(0, |buffer| buffer.size() as usize)
+    }
+}
+
+impl From<&TransitionDescriptor> for IterationTransition {
+    fn from(descriptor: &TransitionDescriptor) -> Self {
+        Self {
+            duration: descriptor.duration(),
+            from: None,
+            start: None,
+            cylinder_instances_buffer: None,
+            render_objects: None,
+        }
+    }
+}
+
+struct LSystemObject {
+    manager: LSystemManagerKey,
     target_iteration: u32,
     active_iteration: Option<u32>,
     render_objects: HashMap<u32, Vec<RenderObject>>,
+    animation: Option<Box<GrowthAnimation>>,
+    transition: Option<Box<IterationTransition>>,
 }
 
 enum Primitive {
@@ -83,9 +265,224 @@ pub struct LSystemScene {
     light_sources: Vec<LightSource>,
     light_sources_bind_group: Option<LightSourcesBindGroup>,
     objects: HashMap<String, SceneObject>,
-    cylinder_mesh: Arc<GpuMesh>,
+    segment_meshes: SegmentMeshes,
     resources: HashMap<String, Resource>,
-    l_systems: HashMap<String, HashMap<String, LSystemManager>>,
+    l_systems: HashMap<LSystemManagerKey, LSystemManager>,
+    evaluation_progress: Vec<EvaluationProgressEvent>,
+    memory_budget: usize,
+    memory_usage: usize,
+    memory_usage_changed: bool,
+    #[cfg(target_arch = "wasm32")]
+    cache_requests: Vec<CacheRequest>,
+}
+
+// prepares render objects for all primitives used by an L-system model, whose cylinders may be
+// replaced by the given ones
+fn build_render_objects(
+    cylinder_instances_buffer: &Buffer<Instance>,
+    model: &LSystemModel,
+    transform_buffer: &Buffer<ModelTransform>,
+    segment_meshes: &mut SegmentMeshes,
+    resources: &mut HashMap<String, Resource>,
+    render_object_creator: &RenderObjectBuilder,
+) -> Vec<RenderObject> {
+    let mut render_objects = vec![render_object_creator.build(
+        &segment_meshes.cylinder,
+        transform_buffer,
+        cylinder_instances_buffer,
+    )];
+    for (&taper, instance_buffer) in model.cone_instances().iter() {
+        render_objects.push(render_object_creator.build(
+            segment_meshes.get_or_create_cone(taper),
+            transform_buffer,
+            instance_buffer,
+        ));
+    }
+    if let Some(instance_buffer) = model.sphere_instances() {
+        render_objects.push(render_object_creator.build(
+            &segment_meshes.sphere,
+            transform_buffer,
+            instance_buffer,
+        ));
+    }
+    if let Some(instance_buffer) = model.circle_instances() {
+        render_objects.push(render_object_creator.build(
+            &segment_meshes.circle,
+            transform_buffer,
+            instance_buffer,
+        ));
+    }
+    for (primitive_id, primitive_instances) in model.primitive_instances().iter() {
+        if let Some(resource) = resources.get_mut(primitive_id) {
+            for (iteration, instance_buffer) in primitive_instances.iter() {
+                render_objects.push(render_object_creator.build(
+                    resource.get_or_create_mesh(iteration),
+                    transform_buffer,
+                    instance_buffer,
+                ));
+            }
+        }
+    }
+    if let Some(surfaces) = model.surfaces() {
+        render_objects.push(render_object_creator.build_with_index_ranges(
+            surfaces.mesh(),
+            transform_buffer,
+            surfaces.instances(),
+            surfaces.index_ranges(),
+        ));
+    }
+    render_objects
+}
+
+// creates a manager for an L-system instance, which is only placed in the instance's environment if
+// the transform of the object it is created for is given
+#[allow(clippy::too_many_arguments)]
+fn create_l_system_manager(
+    l_system: LSystem,
+    l_system_descriptor: &LSystemDescriptor,
+    instance_descriptor: &LSystemInstance,
+    seed: Option<u64>,
+    object_transform: Option<Mat4>,
+    resources: &HashMap<String, Resource>,
+    environments: &HashMap<String, Arc<Mutex<dyn Environment>>>,
+    gpu: &Arc<Gpu>,
+) -> LSystemManager {
+    let mut primitives = HashMap::new();
+    for (primitive_id, primitive_descriptor) in l_system_descriptor.primitives().iter() {
+        if let Some(primitive) = resources.get(primitive_id) {
+            match primitive {
+                Resource::Mesh(mesh_primitive) => {
+                    primitives.insert(
+                        primitive_id.clone(),
+                        LSystemPrimitive::new(
+                            mesh_primitive.mesh.aabb(),
+                            primitive_descriptor.transform(),
+                            primitive_descriptor.material(),
+                        ),
+                    );
+                }
+            }
+        }
+    }
+    // built-in primitives can be used without declaring them
+    for name in BUILTIN_PRIMITIVES {
+        if primitives.contains_key(name) {
+            continue;
+        }
+        if let Some(Resource::Mesh(mesh_primitive)) = resources.get(name) {
+            primitives.insert(
+                name.to_string(),
+                LSystemPrimitive::new(mesh_primitive.mesh.aabb(), None, None),
+            );
+        }
+    }
+    let environment = match (instance_descriptor.environment(), object_transform) {
+        (Some(name), Some(object_transform)) => match environments.get(name) {
+            // the turtle's space is a child of the L-system's space, which is a child of the
+            // object's space
+            Some(environment) => Some(EnvironmentHandle::new(
+                environment.clone(),
+                Mat4::from_translation(instance_descriptor.environment_origin())
+                    * object_transform
+                    * l_system_descriptor.transform().as_mat4(),
+            )),
+            None => {
+                log::error!("Instance references unknown environment: {}", name);
+                None
+            }
+        },
+        _ => None,
+    };
+    LSystemManager::new(
+        l_system,
+        l_system_descriptor.transform(),
+        Some(MaterialState::from(instance_descriptor)),
+        primitives,
+        instance_descriptor.tropism(),
+        seed,
+        environment,
+        instance_descriptor.generalized_cylinders(),
+        instance_descriptor.joint_spheres(),
+        gpu,
+    )
+}
+
+// finds the manager for an L-system object or creates one if the object overrides the seed or the
+// parameters of its L-system instance, or if the instance is placed in an environment
+fn get_or_create_l_system_manager<'a>(
+    object_id: &str,
+    descriptor: &LSystemObjectDescriptor,
+    scene_descriptor: &LSystemSceneDescriptor,
+    l_system_managers: &'a mut HashMap<LSystemManagerKey, LSystemManager>,
+    resources: &HashMap<String, Resource>,
+    environments: &HashMap<String, Arc<Mutex<dyn Environment>>>,
+    gpu: &Arc<Gpu>,
+) -> Option<(LSystemManagerKey, &'a mut LSystemManager)> {
+    let l_system_descriptor = scene_descriptor.l_systems().get(descriptor.system())?;
+    let instance_descriptor = l_system_descriptor.instances().get(descriptor.instance())?;
+    let base_key = LSystemManagerKey::new(
+        descriptor.system(),
+        descriptor.instance(),
+        instance_descriptor.seed(),
+    );
+    let definition = l_system_managers
+        .get(&base_key)?
+        .l_system()
+        .definition()
+        .clone();
+
+    // overrides that don't change the instance's parameters don't require a new manager
+    let mut parameters: Vec<(String, u32)> = descriptor
+        .parameters()
+        .iter()
+        .map(|(name, value)| (name.clone(), value.as_f32().to_bits()))
+        .filter(|(name, bits)| {
+            definition
+                .parameters()
+                .get(name)
+                .map(|value| value.as_f32().to_bits())
+                != Some(*bits)
+        })
+        .collect();
+    parameters.sort();
+    let in_environment = instance_descriptor.environment().is_some();
+    let key = LSystemManagerKey {
+        seed: descriptor.seed().or(instance_descriptor.seed()),
+        parameters,
+        object: in_environment.then(|| object_id.to_string()),
+        ..base_key
+    };
+
+    if !l_system_managers.contains_key(&key) {
+        match LSystem::new(&definition.with_parameters(descriptor.parameters())) {
+            Ok(l_system) => {
+                l_system_managers.insert(
+                    key.clone(),
+                    create_l_system_manager(
+                        l_system,
+                        l_system_descriptor,
+                        instance_descriptor,
+                        key.seed,
+                        in_environment.then(|| descriptor.transform().as_mat4()),
+                        resources,
+                        environments,
+                        gpu,
+                    ),
+                );
+            }
+            Err(error) => {
+                log::error!(
+                    "Could not parse instance {} of L-system {} with overridden parameters: {}",
+                    descriptor.instance(),
+                    descriptor.system(),
+                    error
+                );
+                return None;
+            }
+        }
+    }
+    let manager = l_system_managers.get_mut(&key)?;
+    Some((key, manager))
 }
 
 impl LSystemScene {
@@ -125,14 +522,22 @@ impl LSystemScene {
             ));
         }
 
-        let l_system_cylinder_mesh = Arc::new(GpuMesh::from_mesh::<Vertex>(
-            &Mesh::new_default_cylinder(true),
-            gpu.device(),
-        ));
-
         // parse resources from scene descriptor
         // ignores faulty resources
-        let mut resources: HashMap<String, Resource> = HashMap::new();
+        let mut resources: HashMap<String, Resource> = BUILTIN_PRIMITIVES
+            .iter()
+            .filter_map(|&name| {
+                create_builtin_mesh(name).map(|mesh| {
+                    (
+                        name.to_string(),
+                        Resource::Mesh(MeshResource {
+                            mesh: Arc::new(GpuMesh::from_mesh::<Vertex>(&mesh, gpu.device())),
+                            transform: Transform::default(),
+                        }),
+                    )
+                })
+            })
+            .collect();
         if let Some(scene_resources) = scene_descriptor.resources() {
             for (resource_id, resource) in scene_resources.iter() {
                 match resource {
@@ -157,6 +562,14 @@ impl LSystemScene {
             }
         }
 
+        // parse environments from scene descriptor
+        let environments: HashMap<String, Arc<Mutex<dyn Environment>>> = scene_descriptor
+            .scene()
+            .environments()
+            .iter()
+            .map(|(name, descriptor)| (name.clone(), descriptor.into()))
+            .collect();
+
         // initialize L-system managers for all L-systems in the scene
         // ignores faulty L-systems
         let mut l_system_managers = HashMap::new();
@@ -166,7 +579,6 @@ impl LSystemScene {
                 continue;
             }
             let l_system_descriptor = scene_descriptor.l_systems().get(&name).unwrap();
-            let mut instances = HashMap::new();
             for (instance_name, instance) in system.drain() {
                 if !l_system_descriptor.instances().contains_key(&instance_name) {
                     log::error!("Instance has no descriptor: {}", instance_name);
@@ -174,37 +586,20 @@ impl LSystemScene {
                 }
                 let instance_descriptor =
                     l_system_descriptor.instances().get(&instance_name).unwrap();
-                let mut primitives = HashMap::new();
-                for (primitive_id, primitive_descriptor) in l_system_descriptor.primitives().iter()
-                {
-                    if let Some(primitive) = resources.get(primitive_id) {
-                        match primitive {
-                            Resource::Mesh(mesh_primitive) => {
-                                primitives.insert(
-                                    primitive_id.clone(),
-                                    LSystemPrimitive::new(
-                                        mesh_primitive.mesh.aabb(),
-                                        primitive_descriptor.transform(),
-                                        primitive_descriptor.material(),
-                                    ),
-                                );
-                            }
-                        }
-                    }
-                }
-                instances.insert(
-                    instance_name.to_string(),
-                    LSystemManager::new(
+                l_system_managers.insert(
+                    LSystemManagerKey::new(&name, &instance_name, instance_descriptor.seed()),
+                    create_l_system_manager(
                         instance,
-                        l_system_descriptor.transform(),
-                        Some(MaterialState::from(instance_descriptor)),
-                        primitives,
-                        instance_descriptor.tropism(),
+                        l_system_descriptor,
+                        instance_descriptor,
+                        instance_descriptor.seed(),
+                        None,
+                        &resources,
+                        &environments,
                         gpu,
                     ),
                 );
             }
-            l_system_managers.insert(name, instances);
         }
 
         // parse scene objects from the scene descriptor
@@ -238,23 +633,25 @@ impl LSystemScene {
                             .unwrap()
                             .iterations()
                     };
-                    if !l_system_managers.contains_key(d.system()) {
-                        log::error!("Object references unknown LSystem: {}", d.system());
-                        continue;
-                    } else if !l_system_managers
-                        .get(d.system())
-                        .unwrap()
-                        .contains_key(d.instance())
-                    {
-                        log::error!("Object references unknown instance: {}", d.instance());
+                    let manager = if let Some((key, manager)) = get_or_create_l_system_manager(
+                        object_id,
+                        d,
+                        scene_descriptor,
+                        &mut l_system_managers,
+                        &resources,
+                        &environments,
+                        gpu,
+                    ) {
+                        manager.maybe_increase_target_iteration(iteration);
+                        key
+                    } else {
+                        log::error!(
+                            "Object references unknown instance {} of L-system {}",
+                            d.instance(),
+                            d.system()
+                        );
                         continue;
-                    }
-                    l_system_managers
-                        .get_mut(d.system())
-                        .unwrap()
-                        .get_mut(d.instance())
-                        .unwrap()
-                        .maybe_increase_max_iteration(iteration);
+                    };
                     objects.insert(
                         object_id.to_string(),
                         SceneObject {
@@ -265,11 +662,18 @@ impl LSystemScene {
                                 gpu,
                             ),
                             primitive: Primitive::LSystem(LSystemObject {
-                                system: d.system().to_string(),
-                                instance: d.instance().to_string(),
+                                manager,
                                 target_iteration: iteration,
                                 active_iteration: None,
                                 render_objects: HashMap::new(),
+                                animation: d
+                                    .animation()
+                                    .as_ref()
+                                    .map(|a| Box::new(GrowthAnimation::from(a))),
+                                transition: d
+                                    .transition()
+                                    .as_ref()
+                                    .map(|t| Box::new(IterationTransition::from(t))),
                             }),
                         },
                     );
@@ -313,9 +717,21 @@ impl LSystemScene {
             light_sources,
             light_sources_bind_group: None,
             objects,
-            cylinder_mesh: l_system_cylinder_mesh,
+            segment_meshes: SegmentMeshes::new(gpu),
             resources,
             l_systems: l_system_managers,
+            evaluation_progress: Vec::new(),
+            #[cfg(target_arch = "wasm32")]
+            cache_requests: Vec::new(),
+            memory_budget: (scene_descriptor
+                .scene()
+                .memory_budget()
+                .unwrap_or(DEFAULT_MEMORY_BUDGET)
+                .max(0.)
+                * 1024.
+                * 1024.) as usize,
+            memory_usage: 0,
+            memory_usage_changed: true,
         }
     }
 
@@ -324,7 +740,19 @@ impl LSystemScene {
             .iter()
             .filter_map(|(_, o)| match &o.primitive {
                 Primitive::LSystem(l_system) => {
-                    if !l_system.render_objects.is_empty() {
+                    if let Some(render_objects) = l_system
+                        .animation
+                        .as_ref()
+                        .and_then(|a| a.render_objects.as_ref())
+                    {
+                        Some(render_objects)
+                    } else if let Some(render_objects) = l_system
+                        .transition
+                        .as_ref()
+                        .and_then(|t| t.render_objects.as_ref())
+                    {
+                        Some(render_objects)
+                    } else if !l_system.render_objects.is_empty() {
                         l_system
                             .render_objects
                             .get(&l_system.active_iteration.unwrap())
@@ -358,6 +786,43 @@ impl LSystemScene {
         for (_, o) in self.objects.iter_mut() {
             match &mut o.primitive {
                 Primitive::LSystem(l_system) => {
+                    if let Some(animation) = l_system.animation.as_mut() {
+                        if animation.render_objects.is_none() {
+                            if let Some(model) = animation.model.as_ref() {
+                                animation.render_objects = Some(build_render_objects(
+                                    model.cylinder_instances_buffer(),
+                                    model,
+                                    &o.transform_buffer,
+                                    &mut self.segment_meshes,
+                                    &mut self.resources,
+                                    render_object_creator,
+                                ));
+                            }
+                        }
+                    }
+                    if let Some(transition) = l_system.transition.as_mut() {
+                        if transition.render_objects.is_none() {
+                            if let Some(buffer) = transition.cylinder_instances_buffer.as_ref() {
+                                // cones, spheres, circles, primitives and surfaces are not blended
+                                // but taken from the new iteration
+                                let (_, model) = self
+                                    .l_systems
+                                    .get(&l_system.manager)
+                                    .unwrap_or_else(|| {
+                                        panic!("Unknown L-system manager: {:?}", l_system.manager)
+                                    })
+                                    .try_get_iteration(l_system.target_iteration);
+                                transition.render_objects = Some(build_render_objects(
+                                    buffer,
+                                    model,
+                                    &o.transform_buffer,
+                                    &mut self.segment_meshes,
+                                    &mut self.resources,
+                                    render_object_creator,
+                                ));
+                            }
+                        }
+                    }
                     if !l_system
                         .render_objects
                         .contains_key(&l_system.target_iteration)
@@ -368,10 +833,10 @@ impl LSystemScene {
                         // - the L-system already evaluated an iteration closer to the desired iteration
                         let iteration = self
                             .l_systems
-                            .get(&l_system.system)
-                            .unwrap_or_else(|| panic!("Unknown system: {}", l_system.system))
-                            .get(&l_system.instance)
-                            .unwrap_or_else(|| panic!("Unkown instance: {}", l_system.instance))
+                            .get(&l_system.manager)
+                            .unwrap_or_else(|| {
+                                panic!("Unknown L-system manager: {:?}", l_system.manager)
+                            })
                             .try_get_iteration(l_system.target_iteration);
                         let insert = if let Some(active_iteration) = l_system.active_iteration {
                             active_iteration != iteration.0
@@ -381,27 +846,14 @@ impl LSystemScene {
                         if insert {
                             // prepare render objects for all primitives used by the object's new
                             // L-system iteration and update the object
-                            let cylinder_render_object = render_object_creator.build(
-                                &self.cylinder_mesh,
-                                &o.transform_buffer,
+                            let render_objects = build_render_objects(
                                 iteration.1.cylinder_instances_buffer(),
+                                iteration.1,
+                                &o.transform_buffer,
+                                &mut self.segment_meshes,
+                                &mut self.resources,
+                                render_object_creator,
                             );
-
-                            let mut render_objects = vec![cylinder_render_object];
-                            for (primitive_id, primitive_instances) in
-                                iteration.1.primitive_instances().iter()
-                            {
-                                if let Some(resource) = self.resources.get_mut(primitive_id) {
-                                    for (iteration, instance_buffer) in primitive_instances.iter() {
-                                        render_objects.push(render_object_creator.build(
-                                            resource.get_or_create_mesh(iteration),
-                                            &o.transform_buffer,
-                                            instance_buffer,
-                                        ));
-                                    }
-                                }
-                            }
-
                             l_system.render_objects.insert(iteration.0, render_objects);
                             l_system.active_iteration = Some(iteration.0)
                         }
@@ -425,10 +877,20 @@ impl LSystemScene {
         if let Some(object) = self.objects.get_mut(object_name) {
             match &mut object.primitive {
                 Primitive::LSystem(l_system) => {
+                    if let Some(transition) = l_system.transition.as_mut() {
+                        if l_system.active_iteration.is_some()
+                            && l_system.active_iteration != Some(target_iteration)
+                        {
+                            transition.from = l_system.active_iteration;
+                            transition.start = None;
+                        }
+                    }
                     l_system.target_iteration = target_iteration;
                     if l_system.render_objects.contains_key(&target_iteration) {
                         l_system.active_iteration = Some(target_iteration);
                     }
+                    let key = l_system.manager.clone();
+                    self.update_manager_target_iteration(&key);
                 }
                 _ => log::warn!("Can not set target iteration on non L-System object"),
             }
@@ -437,6 +899,26 @@ impl LSystemScene {
         }
     }
 
+    // evaluation is only continued up to the highest target iteration of all objects sharing a
+    // manager
+    fn update_manager_target_iteration(&mut self, key: &LSystemManagerKey) {
+        let target_iteration = self
+            .objects
+            .values()
+            .filter_map(|o| match &o.primitive {
+                Primitive::LSystem(l_system) if &l_system.manager == key => {
+                    Some(l_system.target_iteration)
+                }
+                _ => None,
+            })
+            .max();
+        if let (Some(target_iteration), Some(manager)) =
+            (target_iteration, self.l_systems.get_mut(key))
+        {
+            manager.set_target_iteration(target_iteration);
+        }
+    }
+
     pub fn camera(&self) -> OrbitCamera {
         self.camera
     }
@@ -452,6 +934,81 @@ impl LSystemScene {
     pub fn aspect_ratio(&self) -> f32 {
         self.aspect_ratio
     }
+    /// Returns the memory occupied by evaluated iterations and animations if it has changed since
+    /// the last call.
+    pub fn take_memory_usage(&mut self) -> Option<MemoryUsageEvent> {
+        if !self.memory_usage_changed {
+            return None;
+        }
+        self.memory_usage_changed = false;
+        Some(MemoryUsageEvent::new(self.memory_usage, self.memory_budget))
+    }
+
+    // evicts the least recently used iterations of all managers until the scene's models fit into
+    // its memory budget, iterations used at the given time are kept
+    // the models of growth animations and the buffers of transitions can't be evicted, but count
+    // towards the budget
+    fn enforce_memory_budget(&mut self, now: f32) {
+        let mut memory_usage: usize = self.l_systems.values().map(|m| m.memory_usage()).sum();
+        for o in self.objects.values() {
+            if let Primitive::LSystem(l_system) = &o.primitive {
+                memory_usage += l_system.animation.as_ref().map_or(0, |a| a.memory_usage())
+                    + l_system.transition.as_ref().map_or(0, |t| t.memory_usage());
+            }
+        }
+        while memory_usage > self.memory_budget {
+            let least_recently_used = self
+                .l_systems
+                .iter()
+                .filter_map(|(key, manager)| {
+                    manager
+                        .least_recently_used(now)
+                        .map(|(iteration, last_used)| (key, iteration, last_used))
+                })
+                .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+                .map(|(key, iteration, _)| (key.clone(), iteration));
+            match least_recently_used {
+                Some((key, iteration)) => {
+                    let manager = self.l_systems.get_mut(&key).unwrap();
+                    memory_usage -= manager.evict(iteration);
+                }
+                None => break,
+            }
+        }
+
+        // render objects of evicted iterations refer to destroyed buffers
+        for o in self.objects.values_mut() {
+            if let Primitive::LSystem(l_system) = &mut o.primitive {
+                if let Some(manager) = self.l_systems.get(&l_system.manager) {
+                    l_system
+                        .render_objects
+                        .retain(|&iteration, _| manager.is_resident(iteration));
+                }
+            }
+        }
+
+        if memory_usage != self.memory_usage {
+            self.memory_usage = memory_usage;
+            self.memory_usage_changed = true;
+        }
+    }
+
+    /// Returns the progress reported by the scene's L-system managers since the last call.
+    pub fn take_evaluation_progress(&mut self) -> Vec<EvaluationProgressEvent> {
+        std::mem::take(&mut self.evaluation_progress)
+    }
+    /// Returns the requests to the page storing cache entries made since the last call.
+    #[cfg(target_arch = "wasm32")]
+    pub fn take_cache_requests(&mut self) -> Vec<CacheRequest> {
+        std::mem::take(&mut self.cache_requests)
+    }
+    /// Passes a cache entry received from the page to the L-system managers.
+    #[cfg(target_arch = "wasm32")]
+    pub fn receive_cache(&mut self, key: &str, data: Option<&Vec<u8>>) {
+        for manager in self.l_systems.values_mut() {
+            manager.receive_cache(key, data.cloned());
+        }
+    }
     pub fn ambient_light(&self) -> LightSource {
         self.ambient_light
     }
@@ -460,9 +1017,126 @@ impl LSystemScene {
 impl Update for LSystemScene {
     fn update(&mut self, input: &Input) {
         self.camera.update(input);
-        for (_, system) in self.l_systems.iter_mut() {
-            for (_, instance) in system.iter_mut() {
-                instance.update(input);
+        for (key, manager) in self.l_systems.iter_mut() {
+            manager.update(input);
+            if let Some(progress) = manager.take_progress() {
+                let mut objects: Vec<String> = self
+                    .objects
+                    .iter()
+                    .filter(
+                        |(_, o)| matches!(&o.primitive, Primitive::LSystem(l) if &l.manager == key),
+                    )
+                    .map(|(name, _)| name.clone())
+                    .collect();
+                objects.sort_unstable();
+                self.evaluation_progress.push(EvaluationProgressEvent::new(
+                    &key.system,
+                    &key.instance,
+                    objects,
+                    &progress,
+                ));
+            }
+            #[cfg(target_arch = "wasm32")]
+            self.cache_requests.extend(manager.take_cache_requests());
+        }
+        let now = input.time().now();
+
+        // mark the iterations used by objects, s.t. they are not evicted
+        for o in self.objects.values() {
+            if let Primitive::LSystem(l_system) = &o.primitive {
+                if let Some(manager) = self.l_systems.get_mut(&l_system.manager) {
+                    manager.touch(l_system.target_iteration, now);
+                    if let Some(active_iteration) = l_system.active_iteration {
+                        manager.touch(active_iteration, now);
+                    }
+                    if let Some(from) = l_system.transition.as_ref().and_then(|t| t.from) {
+                        manager.touch(from, now);
+                    }
+                    if let Some((growing, _)) = l_system.animation.as_ref().and_then(|a| a.shown) {
+                        manager.touch_growth(growing, now);
+                    }
+                }
+            }
+        }
+        self.enforce_memory_budget(now);
+
+        // advance transitions between iterations once both iterations have been evaluated
+        for o in self.objects.values_mut() {
+            if let Primitive::LSystem(l_system) = &mut o.primitive {
+                if let Some(transition) = l_system.transition.as_mut() {
+                    let (from, manager) =
+                        match (transition.from, self.l_systems.get(&l_system.manager)) {
+                            (Some(from), Some(manager)) => (from, manager),
+                            _ => continue,
+                        };
+                    if manager.try_get_iteration(l_system.target_iteration).0
+                        != l_system.target_iteration
+                    {
+                        continue;
+                    }
+                    let elapsed = (now - *transition.start.get_or_insert(now)) / 1000.;
+                    let progress = elapsed / transition.duration.max(f32::EPSILON);
+                    if progress >= 1. {
+                        transition.from = None;
+                        transition.set_cylinder_instances_buffer(None);
+                    } else {
+                        transition.set_cylinder_instances_buffer(manager.blend_iterations(
+                            from,
+                            l_system.target_iteration,
+                            progress,
+                        ));
+                    }
+                }
+            }
+        }
+
+        // advance growth animations, the final iteration is shown for one time unit before an
+        // animation starts over
+        for o in self.objects.values_mut() {
+            if let Primitive::LSystem(l_system) = &mut o.primitive {
+                if let Some(animation) = l_system.animation.as_mut() {
+                    let manager = match self.l_systems.get_mut(&l_system.manager) {
+                        Some(manager) => manager,
+                        None => continue,
+                    };
+                    let end = l_system.target_iteration as f32;
+                    let elapsed = (now - *animation.start.get_or_insert(now)) / 1000.
+                        * animation.iterations_per_second;
+                    let time = if animation.repeat {
+                        (elapsed % (end + 1.)).min(end)
+                    } else {
+                        elapsed.min(end)
+                    };
+                    if animation.time == Some(time) {
+                        continue;
+                    }
+                    let (growing, progress) = match manager.growth_at(time) {
+                        Some(growth) => growth,
+                        None => continue,
+                    };
+                    manager.touch_growth(growing, now);
+                    let (evaluated_iteration, target_model) =
+                        manager.try_get_iteration(l_system.target_iteration);
+                    let bounds = target_model.aabb();
+                    let shown = Some((growing, evaluated_iteration));
+                    let updated = match animation.model.as_mut() {
+                        Some(model) if animation.shown == shown => {
+                            manager.update_growth_model(model, growing, progress, bounds)
+                        }
+                        _ => match manager.growth_model(growing, progress, bounds) {
+                            Some(model) => {
+                                animation.set_model(model);
+                                animation.shown = shown;
+                                true
+                            }
+                            None => false,
+                        },
+                    };
+                    // times beyond the evaluated iterations are updated once they are available
+                    if updated && time <= evaluated_iteration as f32 {
+                        animation.time = Some(time);
+                    }
+                }
             }
         }
     }
@@ -1,5 +1,6 @@
 use crate::framework::scene::camera::CameraView;
 use crate::framework::scene::transform::Transform;
+use crate::lindenmayer::LSystemParameterValue;
 use crate::lsystemrenderer::instancing::Material;
 use crate::lsystemrenderer::l_system_manager::turtle::Tropism;
 use glam::Vec3;
@@ -18,6 +19,19 @@ pub struct LSystemInstance {
     materials: Option<Vec<Material>>,
 
     tropism: Option<Tropism>,
+
+    seed: Option<u64>,
+
+    environment: Option<String>,
+
+    #[serde(rename = "environmentOrigin")]
+    environment_origin: Option<Vec3>,
+
+    #[serde(rename = "generalizedCylinders")]
+    generalized_cylinders: Option<bool>,
+
+    #[serde(rename = "jointSpheres")]
+    joint_spheres: Option<bool>,
 }
 
 impl LSystemInstance {
@@ -36,6 +50,21 @@ impl LSystemInstance {
     pub fn tropism(&self) -> Option<Tropism> {
         self.tropism
     }
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+    pub fn environment(&self) -> Option<&str> {
+        self.environment.as_deref()
+    }
+    pub fn environment_origin(&self) -> Vec3 {
+        self.environment_origin.unwrap_or(Vec3::ZERO)
+    }
+    pub fn generalized_cylinders(&self) -> bool {
+        self.generalized_cylinders.unwrap_or(false)
+    }
+    pub fn joint_spheres(&self) -> bool {
+        self.joint_spheres.unwrap_or(false)
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -185,12 +214,45 @@ impl LightsDescriptor {
     }
 }
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct GrowthAnimationDescriptor {
+    #[serde(rename = "iterationsPerSecond")]
+    iterations_per_second: Option<f32>,
+    #[serde(rename = "loop")]
+    repeat: Option<bool>,
+}
+
+impl GrowthAnimationDescriptor {
+    pub fn iterations_per_second(&self) -> f32 {
+        self.iterations_per_second.unwrap_or(1.)
+    }
+    pub fn repeat(&self) -> bool {
+        self.repeat.unwrap_or(true)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TransitionDescriptor {
+    duration: Option<f32>,
+}
+
+impl TransitionDescriptor {
+    pub fn duration(&self) -> f32 {
+        self.duration.unwrap_or(0.5)
+    }
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct LSystemObjectDescriptor {
     transform: Option<Transform>,
     system: String,
     instance: String,
     iteration: Option<u32>,
+    seed: Option<u64>,
+    #[serde(default)]
+    parameters: HashMap<String, LSystemParameterValue>,
+    animation: Option<GrowthAnimationDescriptor>,
+    transition: Option<TransitionDescriptor>,
 }
 
 impl LSystemObjectDescriptor {
@@ -206,6 +268,18 @@ impl LSystemObjectDescriptor {
     pub fn iteration(&self) -> &Option<u32> {
         &self.iteration
     }
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+    pub fn parameters(&self) -> &HashMap<String, LSystemParameterValue> {
+        &self.parameters
+    }
+    pub fn animation(&self) -> &Option<GrowthAnimationDescriptor> {
+        &self.animation
+    }
+    pub fn transition(&self) -> &Option<TransitionDescriptor> {
+        &self.transition
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -259,11 +333,50 @@ pub enum SceneResource {
     Obj(ObjResource),
 }
 
+#[derive(Clone, Debug, Deserialize)]
+pub struct VoxelShadowDescriptor {
+    #[serde(rename = "voxelSize")]
+    voxel_size: Option<f32>,
+    depth: Option<u32>,
+    strength: Option<f32>,
+    decay: Option<f32>,
+    direction: Option<Vec3>,
+}
+
+impl VoxelShadowDescriptor {
+    pub fn voxel_size(&self) -> Option<f32> {
+        self.voxel_size
+    }
+    pub fn depth(&self) -> Option<u32> {
+        self.depth
+    }
+    pub fn strength(&self) -> Option<f32> {
+        self.strength
+    }
+    pub fn decay(&self) -> Option<f32> {
+        self.decay
+    }
+    pub fn direction(&self) -> Option<Vec3> {
+        self.direction
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type")]
+pub enum EnvironmentDescriptor {
+    #[serde(rename = "voxelShadow")]
+    VoxelShadow(VoxelShadowDescriptor),
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct Scene {
     camera: CameraDescriptor,
     lights: LightsDescriptor,
     objects: HashMap<String, SceneObjectDescriptor>,
+    #[serde(default)]
+    environments: HashMap<String, EnvironmentDescriptor>,
+    #[serde(rename = "memoryBudget")]
+    memory_budget: Option<f32>,
 }
 
 impl Scene {
@@ -276,6 +389,13 @@ impl Scene {
     pub fn objects(&self) -> &HashMap<String, SceneObjectDescriptor> {
         &self.objects
     }
+    pub fn environments(&self) -> &HashMap<String, EnvironmentDescriptor> {
+        &self.environments
+    }
+    /// The memory budget for the models of evaluated L-system iterations in megabytes.
+    pub fn memory_budget(&self) -> Option<f32> {
+        self.memory_budget
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -22,9 +22,18 @@ struct Material {
     shininess: f32,
 }
 
+// ids of the module an instance was created from, u32::MAX if unknown
+struct Lineage {
+    id: u32,
+    parent: u32,
+    production: u32,
+    iteration: u32,
+}
+
 struct Instance {
     transform: ModelTransform,
     material: Material,
+    lineage: Lineage,
 }
 
 struct VertexInput {
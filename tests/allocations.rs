@@ -0,0 +1,86 @@
+// counts the allocations made while evaluating a large module string, which must not grow with
+// the number of modules
+// this is an integration test, s.t. its global allocator doesn't count the allocations of other
+// tests
+use parametric_l_systems::lindenmayer::{LSystem, LSystemDefinition};
+use parametric_l_systems::lsystemrenderer::l_system_manager::evaluator::{Evaluator, Interpreter};
+use parametric_l_systems::lsystemrenderer::l_system_manager::turtle::MaterialState;
+use parametric_l_systems::lsystemrenderer::scene_descriptor::LSystemInstance;
+use serde_json::json;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[test]
+fn evaluates_large_module_strings_without_allocating_per_module() {
+    // context-sensitive productions with conditions calling functions, and branches
+    let definition: LSystemDefinition = serde_json::from_value(json!({
+        "alphabet": ["A(x)", "B(x)", "F(l)", "+(a)", "[", "]"],
+        "parameters": {},
+        "productions": [
+            "A(x) < B(y) : Math.max(x, y, 1) > Math.min(x, 2) -> F(y)+(Math.hypot(x, y))B(y)",
+            "B(y) > [F(l)] : Math.pow(y, 2) >= 0 -> B(y)",
+            "A(x) -> F(1)A(x + 1)[B(x)]A(x)"
+        ],
+        "axiom": "A(0)"
+    }))
+    .unwrap();
+    let mut l_system = LSystem::new(&definition).unwrap();
+    l_system.set_seed(1);
+    let instance: LSystemInstance = serde_json::from_value(json!({
+        "iterations": 1,
+        "materials": [
+            {
+                "type": "Blinn-Phong",
+                "albedo": [1.0, 1.0, 1.0],
+                "specular": [1.0, 1.0, 1.0],
+                "shininess": 30.0
+            }
+        ]
+    }))
+    .unwrap();
+    let interpreter = Interpreter::new(
+        Default::default(),
+        MaterialState::from(&instance),
+        HashMap::new(),
+        None,
+        0,
+        false,
+        false,
+    );
+    let mut evaluator = Evaluator::new(l_system, interpreter, None);
+    let mut modules = 0;
+    while modules < 2_000_000 {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let (_, snapshot) = evaluator.evaluate_next_iteration().unwrap().into_parts();
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+        modules = snapshot.modules().len();
+        assert!(
+            allocations < 2_000.max(modules / 1_000),
+            "{} allocations for {} modules",
+            allocations,
+            modules
+        );
+    }
+}